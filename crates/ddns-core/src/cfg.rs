@@ -3,8 +3,13 @@
 use anyhow::Result;
 use config::builder::{ConfigBuilder, DefaultState};
 use config::{Config, File};
+use ddns_provider::{ProviderTable, RecordType};
 use serde::{Deserialize, de::DeserializeOwned};
-use std::{collections::HashMap, env, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    path::Path,
+};
 use validator::Validate;
 
 /*──────── Provider ────────*/
@@ -19,24 +24,61 @@ pub struct ProviderCfg {
 
     #[serde(default)]
     pub alias: Option<String>,
-    #[serde(default)]
+    /// A | AAAA | TXT | CNAME | HTTPS | SVCB
+    #[serde(default = "default_record_type")]
     pub record_type: String,
     #[serde(default)]
     pub ttl: u32,
-
-    // cloudflare
-    #[serde(default)]
-    pub token: String,
-    // aliyun
+    /// record content template; `{ipv4}` / `{ipv6}` are replaced by the
+    /// detected addresses. Required for non-address types.
     #[serde(default)]
-    pub access_key: Option<String>,
-    #[serde(default)]
-    pub access_secret: Option<String>,
-    #[serde(default)]
-    pub region: Option<String>,
+    pub value: Option<String>,
+
+    /// driver-specific keys (`token`, `access_key`, …), handed to the
+    /// registered `ProviderFactory` as-is
+    #[serde(flatten)]
+    pub params: ProviderTable,
+}
+
+fn default_record_type() -> String {
+    "A".into()
+}
+
+impl ProviderCfg {
+    /// Reject unknown record types and non-address records without `value`.
+    fn check(&self) -> Result<()> {
+        let key = crate::display_key(self);
+        let typ: RecordType = self
+            .record_type
+            .parse()
+            .map_err(|e| anyhow::anyhow!("provider `{key}`: {e}"))?;
+        if !typ.is_address() && self.value.is_none() {
+            anyhow::bail!("provider `{key}`: record_type `{typ}` requires `value`");
+        }
+        Ok(())
+    }
 }
 
 /*──────── Detect ────────*/
+/// Address family a detector (or a record) is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IpFamily {
+    #[serde(alias = "v4")]
+    Ipv4,
+    #[serde(alias = "v6")]
+    Ipv6,
+}
+
+impl std::fmt::Display for IpFamily {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            IpFamily::Ipv4 => "IPv4",
+            IpFamily::Ipv6 => "IPv6",
+        })
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "kind", rename_all = "lowercase")]
 pub enum DetectCfg {
@@ -47,12 +89,18 @@ pub enum DetectCfg {
         timeout: Option<u64>,
         #[serde(default)]
         priority: Option<u32>,
+        /// `None` → used for whichever family the response parses as
+        #[serde(default)]
+        family: Option<IpFamily>,
     },
     Interface {
         /// network interface name, e.g. `eth0`
         iface: String,
         #[serde(default)]
         priority: Option<u32>,
+        /// `None` → used for both families
+        #[serde(default)]
+        family: Option<IpFamily>,
     },
     Command {
         /// arbitrary shell command that outputs the public IP
@@ -62,9 +110,31 @@ pub enum DetectCfg {
         timeout: Option<u64>,
         #[serde(default)]
         priority: Option<u32>,
+        /// `None` → used for whichever family the output parses as
+        #[serde(default)]
+        family: Option<IpFamily>,
     },
 }
 
+impl DetectCfg {
+    /// Default priority is 100 if unspecified.
+    pub fn priority(&self) -> u32 {
+        match self {
+            DetectCfg::Http { priority, .. }
+            | DetectCfg::Interface { priority, .. }
+            | DetectCfg::Command { priority, .. } => priority.unwrap_or(100),
+        }
+    }
+
+    pub fn family(&self) -> Option<IpFamily> {
+        match self {
+            DetectCfg::Http { family, .. }
+            | DetectCfg::Interface { family, .. }
+            | DetectCfg::Command { family, .. } => *family,
+        }
+    }
+}
+
 /*──────── Scheduler ────────*/
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct SchedulerCfg {
@@ -72,6 +142,16 @@ pub struct SchedulerCfg {
     pub cron: Option<String>,
     /// max concurrent provider updates
     pub concurrency: Option<usize>,
+    /// seconds after which an unchanged record is pushed again;
+    /// `None` → once a day, `0` → push on every tick
+    pub force_refresh_interval: Option<u64>,
+}
+
+/*──────── State ────────*/
+#[derive(Debug, Clone, Deserialize)]
+pub struct StateCfg {
+    /// state file; `.toml` → TOML, otherwise JSON
+    pub path: String,
 }
 
 /*──────── HTTP ────────*/
@@ -132,6 +212,8 @@ struct Root {
     #[serde(default)]
     scheduler: Option<SchedulerCfg>,
     #[serde(default)]
+    state: Option<StateCfg>,
+    #[serde(default)]
     detect: Vec<DetectCfg>,
     provider: Vec<ProviderCfg>,
 }
@@ -140,6 +222,7 @@ struct Root {
 pub struct AppConfig {
     pub http: HttpCfg,
     pub scheduler: SchedulerCfg,
+    pub state: Option<StateCfg>,
     pub detect: Vec<DetectCfg>,
     pub provider: Vec<ProviderCfg>,
 }
@@ -271,11 +354,59 @@ pub fn load_config(path: &str) -> Result<AppConfig> {
         root.provider = v;
     }
 
-    // 5) lift into AppConfig
+    // 5) reject bad provider entries early; an `alias` identifies its entry
+    //    in status and state, so it must be unique
+    let mut aliases = HashSet::new();
+    for p in &root.provider {
+        p.check()?;
+        if let Some(alias) = &p.alias
+            && !aliases.insert(alias)
+        {
+            anyhow::bail!("alias `{alias}` is used by more than one provider entry");
+        }
+    }
+
+    // 6) lift into AppConfig
     Ok(AppConfig {
         http: root.http.unwrap_or_default(),
         scheduler: root.scheduler.unwrap_or_default(),
+        state: root.state,
         detect: root.detect,
         provider: root.provider,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provider_keys() {
+        let entry = |extra: &str| {
+            format!(
+                "[[provider]]\nkind = \"memory\"\nzone = \"example.test\"\nrecord = \"home\"\n{extra}\n"
+            )
+        };
+        let path = env::temp_dir().join(format!("ddns-cfg-{}.toml", std::process::id()));
+        let load = |body: String| {
+            std::fs::write(&path, body).unwrap();
+            load_config(path.to_str().unwrap())
+        };
+
+        // the same kind twice without `alias` (A + AAAA) gets distinct keys
+        let cfg = load(entry("") + &entry("record_type = \"aaaa\"")).unwrap();
+        let keys: Vec<_> = cfg.provider.iter().map(crate::display_key).collect();
+        assert_eq!(
+            keys,
+            [
+                "memory:home.example.test/A",
+                "memory:home.example.test/AAAA"
+            ]
+        );
+
+        let err = load(entry("alias = \"home\"") + &entry("alias = \"home\"")).unwrap_err();
+        assert!(err.to_string().contains("alias `home`"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -3,35 +3,63 @@
 //! * HTTP       – cross-platform  
 //! * Command    – cross-platform  
 //! * Interface  – uses `pnet_datalink` on Unix; not supported on Windows
+//!
+//! IPv4 and IPv6 are resolved independently; a detector tagged with
+//! `family` only takes part in that family's chain.
 
-use crate::cfg::DetectCfg;
+use crate::cfg::{DetectCfg, IpFamily};
 use anyhow::{Result, anyhow};
 use reqwest::Client;
-use std::time::Duration;
+use std::{net::IpAddr, time::Duration};
 use tokio::{process::Command, time::timeout};
-use tracing::info;
+use tracing::{debug, info};
+
+/*──────── family helpers ────────*/
+fn in_family(ip: &IpAddr, family: IpFamily) -> bool {
+    matches!(
+        (ip, family),
+        (IpAddr::V4(_), IpFamily::Ipv4) | (IpAddr::V6(_), IpFamily::Ipv6)
+    )
+}
+
+/// Parse detector output and make sure it belongs to the requested family.
+fn parse_for(raw: &str, family: IpFamily) -> Result<IpAddr> {
+    let ip: IpAddr = raw
+        .parse()
+        .map_err(|_| anyhow!("`{raw}` is not an IP address"))?;
+    if in_family(&ip, family) {
+        Ok(ip)
+    } else {
+        Err(anyhow!("`{ip}` is not an {family} address"))
+    }
+}
 
 /*──────── interface detector (platform split) ────────*/
 #[cfg(unix)]
-fn detect_iface(iface: &str) -> Result<String> {
+fn detect_iface(iface: &str, family: IpFamily) -> Result<IpAddr> {
     use pnet_datalink::interfaces;
-    use std::net::IpAddr;
 
     for i in interfaces() {
         if i.name == iface {
             for ipn in i.ips {
-                if let IpAddr::V4(v4) = ipn.ip() {
-                    return Ok(v4.to_string());
+                let ip = ipn.ip();
+                let usable = match ip {
+                    IpAddr::V4(v4) => !v4.is_loopback(),
+                    // skip link-local (fe80::/10) – never routable
+                    IpAddr::V6(v6) => !v6.is_loopback() && (v6.segments()[0] & 0xffc0) != 0xfe80,
+                };
+                if usable && in_family(&ip, family) {
+                    return Ok(ip);
                 }
             }
-            return Err(anyhow!("interface `{iface}` has no IPv4 address"));
+            return Err(anyhow!("interface `{iface}` has no {family} address"));
         }
     }
     Err(anyhow!("interface `{iface}` not found"))
 }
 
 #[cfg(windows)]
-fn detect_iface(_iface: &str) -> Result<String> {
+fn detect_iface(_iface: &str, _family: IpFamily) -> Result<IpAddr> {
     Err(anyhow!(
         r#"kind = "interface" is not supported on Windows; \
 please use `http` or `command` instead"#
@@ -39,72 +67,71 @@ please use `http` or `command` instead"#
 }
 
 /*──────── HTTP detector ────────*/
-async fn detect_http(url: &str, to: Option<u64>) -> Result<String> {
+async fn detect_http(url: &str, to: Option<u64>, family: IpFamily) -> Result<IpAddr> {
     let fut = async {
-        Ok::<_, anyhow::Error>(
-            Client::new()
-                .get(url)
-                .send()
-                .await?
-                .text()
-                .await?
-                .trim()
-                .to_owned(),
-        )
+        let body = Client::new().get(url).send().await?.text().await?;
+        parse_for(body.trim(), family)
     };
     match to {
-        Some(ms) => Ok(timeout(Duration::from_millis(ms), fut).await??),
+        Some(ms) => timeout(Duration::from_millis(ms), fut).await?,
         None => fut.await,
     }
 }
 
 /*──────── Command detector ────────*/
-async fn detect_cmd(cmd: &str, to: Option<u64>) -> Result<String> {
+async fn detect_cmd(cmd: &str, to: Option<u64>, family: IpFamily) -> Result<IpAddr> {
     let fut = async {
         let out = Command::new("sh").arg("-c").arg(cmd).output().await?;
-        Ok(String::from_utf8_lossy(&out.stdout).trim().to_owned())
+        parse_for(String::from_utf8_lossy(&out.stdout).trim(), family)
     };
     match to {
-        Some(ms) => Ok(timeout(Duration::from_millis(ms), fut).await??),
+        Some(ms) => timeout(Duration::from_millis(ms), fut).await?,
         None => fut.await,
     }
 }
 
 /*──────── orchestrator ────────*/
-pub async fn detect_ip(list: &[DetectCfg]) -> Result<String> {
-    // default priority is 100 if unspecified
-    let mut items = list.to_vec();
-    items.sort_by_key(|d| match d {
-        DetectCfg::Http { priority, .. }
-        | DetectCfg::Interface { priority, .. }
-        | DetectCfg::Command { priority, .. } => priority.unwrap_or(100),
-    });
+
+/// Run the detector chain for one address family.
+///
+/// Detectors tagged with the other family are skipped; untagged ones are
+/// tried and their result is accepted only if it parses as `family`.
+pub async fn detect_ip(list: &[DetectCfg], family: IpFamily) -> Result<IpAddr> {
+    let mut items: Vec<_> = list
+        .iter()
+        .filter(|d| d.family().is_none_or(|f| f == family))
+        .cloned()
+        .collect();
+    items.sort_by_key(DetectCfg::priority);
 
     for det in items {
         match det {
             DetectCfg::Http {
                 url, timeout: to, ..
-            } => {
-                if let Ok(ip) = detect_http(&url, to).await {
+            } => match detect_http(&url, to, family).await {
+                Ok(ip) => {
                     info!("detect/http {url} -> {ip}");
                     return Ok(ip);
                 }
-            }
-            DetectCfg::Interface { iface, .. } => {
-                if let Ok(ip) = detect_iface(&iface) {
+                Err(e) => debug!("detect/http {url} ({family}): {e}"),
+            },
+            DetectCfg::Interface { iface, .. } => match detect_iface(&iface, family) {
+                Ok(ip) => {
                     info!("detect/iface {iface} -> {ip}");
                     return Ok(ip);
                 }
-            }
+                Err(e) => debug!("detect/iface {iface} ({family}): {e}"),
+            },
             DetectCfg::Command {
                 cmd, timeout: to, ..
-            } => {
-                if let Ok(ip) = detect_cmd(&cmd, to).await {
+            } => match detect_cmd(&cmd, to, family).await {
+                Ok(ip) => {
                     info!("detect/cmd `{cmd}` -> {ip}");
                     return Ok(ip);
                 }
-            }
+                Err(e) => debug!("detect/cmd `{cmd}` ({family}): {e}"),
+            },
         }
     }
-    Err(anyhow!("all detectors failed"))
+    Err(anyhow!("all {family} detectors failed"))
 }
@@ -4,16 +4,37 @@ pub mod cfg;
 pub mod detector;
 pub mod error;
 mod http;
+pub mod registry;
 pub mod scheduler;
+pub mod state;
 pub mod status;
 
 use anyhow::Result;
 use cfg::AppConfig;
+use state::StateStore;
 use status::{Event, SharedStatus};
+use std::sync::Arc;
 
-/// Launches HTTP dashboard and scheduler concurrently.
+/// Launches HTTP dashboard and scheduler concurrently with the built-in
+/// providers.
 pub async fn bootstrap(cfg: AppConfig) -> Result<()> {
+    bootstrap_with(cfg, ProviderRegistry::new()).await
+}
+
+/// Like [`bootstrap`], resolving `kind` through a caller-supplied registry.
+///
+/// When `[state]` is configured the file is loaded first, so history and
+/// provider caches survive restarts.
+pub async fn bootstrap_with(cfg: AppConfig, registry: ProviderRegistry) -> Result<()> {
     let shared: SharedStatus = Default::default();
+    let store = match &cfg.state {
+        Some(s) => {
+            let store = StateStore::open(s)?;
+            store.seed(&shared);
+            Some(Arc::new(store))
+        }
+        None => None,
+    };
     let (tx, _rx) = tokio::sync::broadcast::channel::<Event>(1024);
 
     let http_cfg = cfg.http.clone();
@@ -21,7 +42,7 @@ pub async fn bootstrap(cfg: AppConfig) -> Result<()> {
     let sched_bus = tx.clone();
 
     tokio::try_join!(
-        scheduler::run_scheduler(cfg, sched_shared, sched_bus),
+        scheduler::run_scheduler(cfg, sched_shared, sched_bus, store, &registry),
         http::run_http_server(shared, tx, http_cfg)
     )?;
 
@@ -29,3 +50,5 @@ pub async fn bootstrap(cfg: AppConfig) -> Result<()> {
 }
 
 pub use cfg::load_config;
+pub use registry::ProviderRegistry;
+pub use scheduler::display_key;
@@ -0,0 +1,109 @@
+//! Provider registry – maps `kind` strings to [`ProviderFactory`]s
+//!
+//! Built-in drivers are registered according to cargo features; embedders
+//! add their own with [`ProviderRegistry::register`] and start the daemon
+//! through [`crate::bootstrap_with`].
+
+use crate::cfg::ProviderCfg;
+use anyhow::Result;
+use ddns_provider::{DnsProvider, ProviderFactory, ProviderSpec};
+use std::{collections::BTreeMap, sync::Arc};
+
+pub struct ProviderRegistry {
+    factories: BTreeMap<String, Arc<dyn ProviderFactory>>,
+}
+
+impl Default for ProviderRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProviderRegistry {
+    /// Registry pre-filled with every built-in driver enabled at compile time.
+    #[allow(unused_mut)]
+    pub fn new() -> Self {
+        let mut r = Self::empty();
+        #[cfg(feature = "ddns-provider-cloudflare")]
+        r.register(ddns_provider_cloudflare::CfFactory);
+        #[cfg(feature = "ddns-provider-aliyun")]
+        r.register(ddns_provider_aliyun::AliFactory);
+        #[cfg(feature = "ddns-provider-exec")]
+        r.register(ddns_provider_exec::ExecFactory);
+        #[cfg(feature = "ddns-provider-memory")]
+        r.register(ddns_provider_memory::MemoryFactory);
+        #[cfg(feature = "ddns-provider-rfc2136")]
+        r.register(ddns_provider_rfc2136::Rfc2136Factory);
+        #[cfg(feature = "ddns-provider-dnspod")]
+        r.register(ddns_provider_dnspod::DnspodFactory);
+        #[cfg(feature = "ddns-provider-route53")]
+        r.register(ddns_provider_route53::Route53Factory);
+        #[cfg(feature = "ddns-provider-dyndns2")]
+        r.register(ddns_provider_dyndns2::DynDns2Factory);
+        #[cfg(feature = "ddns-provider-duckdns")]
+        r.register(ddns_provider_duckdns::DuckDnsFactory);
+        #[cfg(feature = "ddns-provider-gcloud")]
+        r.register(ddns_provider_gcloud::GcloudFactory);
+        #[cfg(feature = "ddns-provider-azure")]
+        r.register(ddns_provider_azure::AzureFactory);
+        #[cfg(feature = "ddns-provider-digitalocean")]
+        r.register(ddns_provider_digitalocean::DigitalOceanFactory);
+        #[cfg(feature = "ddns-provider-hetzner")]
+        r.register(ddns_provider_hetzner::HetznerFactory);
+        #[cfg(feature = "ddns-provider-linode")]
+        r.register(ddns_provider_linode::LinodeFactory);
+        #[cfg(feature = "ddns-provider-powerdns")]
+        r.register(ddns_provider_powerdns::PowerDnsFactory);
+        #[cfg(feature = "ddns-provider-gandi")]
+        r.register(ddns_provider_gandi::GandiFactory);
+        #[cfg(feature = "ddns-provider-porkbun")]
+        r.register(ddns_provider_porkbun::PorkbunFactory);
+        #[cfg(feature = "ddns-provider-ovh")]
+        r.register(ddns_provider_ovh::OvhFactory);
+        #[cfg(feature = "ddns-provider-huawei")]
+        r.register(ddns_provider_huawei::HuaweiFactory);
+        #[cfg(feature = "ddns-provider-desec")]
+        r.register(ddns_provider_desec::DesecFactory);
+        #[cfg(feature = "ddns-provider-webhook")]
+        r.register(ddns_provider_webhook::WebhookFactory);
+        #[cfg(feature = "ddns-provider-zonefile")]
+        r.register(ddns_provider_zonefile::ZoneFileFactory);
+        #[cfg(feature = "ddns-provider-hosts")]
+        r.register(ddns_provider_hosts::HostsFactory);
+        r
+    }
+
+    /// Registry without any driver.
+    pub fn empty() -> Self {
+        Self {
+            factories: BTreeMap::new(),
+        }
+    }
+
+    /// Add a driver; an existing one with the same `kind` is replaced.
+    pub fn register(&mut self, factory: impl ProviderFactory + 'static) -> &mut Self {
+        self.factories
+            .insert(factory.kind().to_ascii_lowercase(), Arc::new(factory));
+        self
+    }
+
+    /// Registered `kind` names, sorted.
+    pub fn kinds(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(String::as_str)
+    }
+
+    /// Construct the driver for one `[[provider]]` entry.
+    pub fn build(&self, p: &ProviderCfg) -> Result<Arc<dyn DnsProvider>> {
+        let kind = p.kind.to_ascii_lowercase();
+        let Some(factory) = self.factories.get(&kind) else {
+            anyhow::bail!("unknown provider kind `{kind}`");
+        };
+        let spec = ProviderSpec {
+            zone: &p.zone,
+            record: &p.record,
+            record_type: &p.record_type,
+            ttl: p.ttl,
+        };
+        factory.build(&spec, &p.params)
+    }
+}
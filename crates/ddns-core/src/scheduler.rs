@@ -3,25 +3,32 @@
 //! Since 2025-05-31 the `next_tick` timestamp is written for the dashboard.
 
 use crate::{
-    cfg::{AppConfig, ProviderCfg},
+    cfg::{AppConfig, IpFamily, ProviderCfg},
     detector::detect_ip,
+    registry::ProviderRegistry,
+    state::{PersistedState, ProviderState, StateStore},
     status::{Event, EventBus, SharedStatus},
 };
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use cron::Schedule;
-use ddns_provider::DnsProvider;
-use std::{future::Future, pin::Pin, str::FromStr, sync::Arc, time::Duration};
+use ddns_provider::{DnsProvider, ProviderError, RecordType};
+use std::{
+    collections::HashMap, future::Future, net::IpAddr, pin::Pin, str::FromStr, sync::Arc,
+    time::Duration,
+};
 use tokio::{
     sync::{Notify, Semaphore},
     task::JoinHandle,
     time::sleep,
 };
 use tokio_cron_scheduler::{Job, JobScheduler};
-use tracing::{error, info};
+use tracing::{debug, error, info};
 
 const MAX_RETRY: u8 = 5; // max retries per provider
 const BACKOFF_SECS: u64 = 5; // exponential back-off base (seconds)
+const MAX_WAIT_SECS: u64 = BACKOFF_SECS << MAX_RETRY; // longest wait inside one cycle
+const FORCE_REFRESH_SECS: u64 = 24 * 60 * 60; // default forced refresh (seconds)
 
 /*──────── Provider wrapper ────────*/
 #[derive(Clone)]
@@ -29,11 +36,69 @@ struct ProviderEntry {
     key: String,
     prov: Arc<dyn DnsProvider>,
     ttl: u32,
+    /// `value` template from config; `None` → the detected address
+    value: Option<String>,
+}
+
+impl ProviderEntry {
+    /// Address families this record needs detected.
+    fn families(&self) -> Vec<IpFamily> {
+        match &self.value {
+            Some(tpl) => [IpFamily::Ipv4, IpFamily::Ipv6]
+                .into_iter()
+                .filter(|f| tpl.contains(placeholder(*f)))
+                .collect(),
+            None => family_of(self.prov.record_type()).into_iter().collect(),
+        }
+    }
+
+    /// Value to push, or the first family that was not detected.
+    fn render(&self, ips: &HashMap<IpFamily, String>) -> Result<String, IpFamily> {
+        match &self.value {
+            Some(tpl) => self.families().into_iter().try_fold(tpl.clone(), |out, f| {
+                let ip = ips.get(&f).ok_or(f)?;
+                Ok(out.replace(placeholder(f), ip))
+            }),
+            None => {
+                let f = family_of(self.prov.record_type())
+                    .expect("non-address records require `value` (checked at config load)");
+                ips.get(&f).cloned().ok_or(f)
+            }
+        }
+    }
+}
+
+/// Address family an `A` / `AAAA` record has to be fed with.
+fn family_of(typ: RecordType) -> Option<IpFamily> {
+    match typ {
+        RecordType::A => Some(IpFamily::Ipv4),
+        RecordType::AAAA => Some(IpFamily::Ipv6),
+        _ => None,
+    }
+}
+
+/// Template placeholder replaced by the detected address of `f`.
+fn placeholder(f: IpFamily) -> &'static str {
+    match f {
+        IpFamily::Ipv4 => "{ipv4}",
+        IpFamily::Ipv6 => "{ipv6}",
+    }
+}
+
+/// Identity of the record a provider manages; guards restored caches.
+fn target_of(prov: &dyn DnsProvider) -> String {
+    format!("{}.{}/{:?}", prov.record(), prov.zone(), prov.record_type())
 }
 
 /*──────── entry point ────────*/
-pub async fn run_scheduler(cfg: AppConfig, status: SharedStatus, bus: EventBus) -> Result<()> {
-    let providers = Arc::new(init_providers(&cfg, &status)?);
+pub async fn run_scheduler(
+    cfg: AppConfig,
+    status: SharedStatus,
+    bus: EventBus,
+    state: Option<Arc<StateStore>>,
+    registry: &ProviderRegistry,
+) -> Result<()> {
+    let providers = Arc::new(init_providers(&cfg, &status, state.as_deref(), registry)?);
     let sem = Arc::new(Semaphore::new(cfg.scheduler.concurrency.unwrap_or(4)));
 
     /* parse cron expression (if any) */
@@ -52,6 +117,7 @@ pub async fn run_scheduler(cfg: AppConfig, status: SharedStatus, bus: EventBus)
         let status = status.clone();
         let bus = bus.clone();
         let cron_sched = cron_sched.clone();
+        let state = state.clone();
         move || {
             let cfg = cfg.clone();
             let providers = providers.clone();
@@ -59,12 +125,25 @@ pub async fn run_scheduler(cfg: AppConfig, status: SharedStatus, bus: EventBus)
             let status = status.clone();
             let bus = bus.clone();
             let cron_sched = cron_sched.clone();
+            let state = state.clone();
             Box::pin(async move {
-                if let Err(e) =
-                    one_cycle(&cfg, &providers, sem, status, bus, cron_sched.as_ref()).await
+                if let Err(e) = one_cycle(
+                    &cfg,
+                    &providers,
+                    sem,
+                    status.clone(),
+                    bus,
+                    cron_sched.as_ref(),
+                )
+                .await
                 {
                     error!("{e:?}");
                 }
+                if let Some(store) = state
+                    && let Err(e) = store.save(&snapshot(&status, &providers))
+                {
+                    error!("state save failed: {e:?}");
+                }
             })
         }
     });
@@ -92,23 +171,62 @@ async fn one_cycle(
     bus: EventBus,
     cron_sched: Option<&Arc<Schedule>>,
 ) -> Result<()> {
-    let ip = detect_ip(&cfg.detect).await?;
-    info!("detected public IP = {ip}");
+    /* detect each family that at least one provider needs */
+    let mut ips: HashMap<IpFamily, String> = HashMap::new();
+    let mut needed = 0;
+    for family in [IpFamily::Ipv4, IpFamily::Ipv6] {
+        if !providers.iter().any(|e| e.families().contains(&family)) {
+            continue;
+        }
+        needed += 1;
+        match detect_ip(&cfg.detect, family).await {
+            Ok(ip) => {
+                info!("detected public {family} = {ip}");
+                let _ = bus.send(Event::Log(format!("detected {family} {ip}")));
+                ips.insert(family, ip.to_string());
+            }
+            Err(e) => {
+                error!("{e:?}");
+                let _ = bus.send(Event::Log(format!("{family} detection failed: {e}")));
+            }
+        }
+    }
 
     /* write status */
     {
         let mut st = status.write();
         st.now = Utc::now();
-        st.current_ip = Some(ip.clone());
+        st.current_ip = ips.get(&IpFamily::Ipv4).cloned();
+        st.current_ipv6 = ips.get(&IpFamily::Ipv6).cloned();
         st.next_tick = cron_sched.and_then(|s| s.after(&st.now).next());
     }
     let _ = bus.send(Event::Status(status.read().clone()));
-    let _ = bus.send(Event::Log(format!("detected IP {ip}")));
 
-    /* update providers concurrently */
+    if needed > 0 && ips.is_empty() {
+        anyhow::bail!("no public IP detected for any address family");
+    }
+
+    /* update providers concurrently; skip those missing a detected family */
+    let refresh = cfg
+        .scheduler
+        .force_refresh_interval
+        .unwrap_or(FORCE_REFRESH_SECS);
     let mut handles: Vec<JoinHandle<()>> = Vec::new();
     for entry in providers.iter().cloned() {
-        let ip = ip.clone();
+        let ip = match entry.render(&ips) {
+            Ok(v) => v,
+            Err(family) => {
+                let msg = format!("no {family} address detected");
+                set_stat(&status, &entry.key, None, Some(msg.clone()));
+                let _ = bus.send(Event::Log(format!("{} skipped: {msg}", entry.key)));
+                continue;
+            }
+        };
+        if is_fresh(&status, &entry.key, &ip, refresh) {
+            debug!("{} unchanged ({ip}); skip", entry.key);
+            let _ = bus.send(Event::Log(format!("{} unchanged", entry.key)));
+            continue;
+        }
         let sem = sem.clone();
         let status = status.clone();
         let bus = bus.clone();
@@ -130,7 +248,33 @@ async fn retry_update(
     status: SharedStatus,
     bus: EventBus,
 ) {
-    let ProviderEntry { key, prov, ttl } = entry;
+    let ProviderEntry { key, prov, ttl, .. } = entry;
+
+    /* read-before-write: nothing to do when DNS already holds `ip` */
+    let live = {
+        let _permit = sem.acquire().await.unwrap();
+        prov.get_record(prov.zone(), prov.record(), prov.record_type())
+            .await
+    };
+    match live {
+        Ok(Some(v)) if same_value(prov.record_type(), &v, ip) => {
+            set_stat(&status, &key, Some((Utc::now(), ip)), None);
+            let _ = bus.send(Event::Status(status.read().clone()));
+            let _ = bus.send(Event::Log(format!("{key} already up to date")));
+            return;
+        }
+        Ok(v) => {
+            status
+                .write()
+                .providers
+                .entry(key.clone())
+                .or_default()
+                .live_value = v
+        }
+        Err(ProviderError::Unsupported(_)) => {}
+        Err(e) => debug!("{key} read failed: {e}; writing anyway"),
+    }
+
     let mut attempt = 0;
     loop {
         let res = {
@@ -141,16 +285,31 @@ async fn retry_update(
 
         match res {
             Ok(_) => {
-                set_stat(&status, &key, Some(Utc::now()), None);
+                set_stat(&status, &key, Some((Utc::now(), ip)), None);
                 let _ = bus.send(Event::Status(status.read().clone()));
                 let _ = bus.send(Event::Log(format!("{key} OK")));
                 break;
             }
-            Err(e) if attempt < MAX_RETRY => {
+            // permanent failures (auth, not found, invalid input) are not retried;
+            // rate limits wait as long as the server asks, up to MAX_WAIT_SECS
+            Err(e) if attempt < MAX_RETRY && e.is_retryable() => {
                 attempt += 1;
-                let wait = BACKOFF_SECS << attempt;
-                error!("{key} retry {attempt}/{MAX_RETRY} failed: {e}; waiting {wait}s");
-                sleep(Duration::from_secs(wait)).await;
+                let wait = e
+                    .retry_after()
+                    .unwrap_or(Duration::from_secs(BACKOFF_SECS << attempt));
+                if wait > Duration::from_secs(MAX_WAIT_SECS) {
+                    // don't stall the cycle; the next tick tries again
+                    let msg = format!("{e}; deferred to the next cycle");
+                    set_stat(&status, &key, None, Some(msg.clone()));
+                    let _ = bus.send(Event::Status(status.read().clone()));
+                    let _ = bus.send(Event::Log(format!("{key} {msg}")));
+                    break;
+                }
+                error!(
+                    "{key} retry {attempt}/{MAX_RETRY} failed: {e}; waiting {}s",
+                    wait.as_secs()
+                );
+                sleep(wait).await;
             }
             Err(e) => {
                 set_stat(&status, &key, None, Some(e.to_string()));
@@ -163,7 +322,12 @@ async fn retry_update(
 }
 
 /*──────── Provider initialization ────────*/
-fn init_providers(cfg: &AppConfig, status: &SharedStatus) -> Result<Vec<ProviderEntry>> {
+fn init_providers(
+    cfg: &AppConfig,
+    status: &SharedStatus,
+    state: Option<&StateStore>,
+    registry: &ProviderRegistry,
+) -> Result<Vec<ProviderEntry>> {
     /* ensure keys exist in shared status */
     {
         let mut st = status.write();
@@ -174,61 +338,243 @@ fn init_providers(cfg: &AppConfig, status: &SharedStatus) -> Result<Vec<Provider
 
     let mut v = Vec::new();
     for p in &cfg.provider {
-        let prov: Arc<dyn DnsProvider> = match p.kind.to_ascii_lowercase().as_str() {
-            #[cfg(feature = "ddns-provider-cloudflare")]
-            "cloudflare" => Arc::new(ddns_provider_cloudflare::CfProvider::new(
-                &p.zone,
-                &p.record,
-                &p.record_type,
-                p.ttl,
-                &p.token,
-            )?),
-
-            #[cfg(feature = "ddns-provider-aliyun")]
-            "aliyun" => {
-                let ak = p
-                    .access_key
-                    .as_deref()
-                    .ok_or_else(|| anyhow::anyhow!("aliyun: access_key missing"))?;
-                let sk = p
-                    .access_secret
-                    .as_deref()
-                    .ok_or_else(|| anyhow::anyhow!("aliyun: access_secret missing"))?;
-                let region = p.region.as_deref().unwrap_or("cn-hangzhou");
-                Arc::new(ddns_provider_aliyun::AliProvider::new(
-                    &p.zone,
-                    &p.record,
-                    &p.record_type,
-                    p.ttl,
-                    ak,
-                    sk,
-                    region,
-                )?)
-            }
-            other => anyhow::bail!("unknown provider kind `{other}`"),
-        };
+        let prov = registry.build(p)?;
+        if let Some(cache) = state.and_then(|s| s.cache_for(&display_key(p), &target_of(&*prov))) {
+            prov.restore_cache(cache);
+        }
         v.push(ProviderEntry {
             key: display_key(p),
             prov,
             ttl: p.ttl,
+            value: p.value.clone(),
         });
     }
     Ok(v)
 }
 
-fn display_key(p: &ProviderCfg) -> String {
-    p.alias.clone().unwrap_or_else(|| p.kind.clone())
+/// Label used in the dashboard and state file: `alias`, falling back to
+/// `kind:record.zone/TYPE` so that entries without one never collide.
+pub fn display_key(p: &ProviderCfg) -> String {
+    match &p.alias {
+        Some(alias) => alias.clone(),
+        None => format!(
+            "{}:{}.{}/{}",
+            p.kind,
+            p.record,
+            p.zone.trim_end_matches('.'),
+            p.record_type.to_ascii_uppercase()
+        ),
+    }
+}
+
+/*──────── status helpers ────────*/
+
+/// Compare a live DNS value with the wanted one, ignoring representation
+/// differences: IPs are compared parsed (`2001:DB8::1` == `2001:db8::1`),
+/// CNAME targets without trailing dot and case, TXT without quotes, SVCB
+/// token by token with quoted param values unquoted (`alpn="h2"` == `alpn=h2`).
+fn same_value(typ: RecordType, live: &str, want: &str) -> bool {
+    match typ {
+        RecordType::A | RecordType::AAAA => {
+            match (live.parse::<IpAddr>(), want.parse::<IpAddr>()) {
+                (Ok(a), Ok(b)) => a == b,
+                _ => live == want,
+            }
+        }
+        RecordType::CNAME => live
+            .trim_end_matches('.')
+            .eq_ignore_ascii_case(want.trim_end_matches('.')),
+        RecordType::TXT => live.trim_matches('"') == want.trim_matches('"'),
+        RecordType::HTTPS | RecordType::SVCB => {
+            let unquote = |t: &str| match t.split_once('=') {
+                Some((k, v)) => format!("{k}={}", v.trim_matches('"')),
+                None => t.to_owned(),
+            };
+            live.split_whitespace()
+                .map(unquote)
+                .eq(want.split_whitespace().map(unquote))
+        }
+    }
+}
+
+/// Collect shared status and provider caches into the on-disk layout.
+fn snapshot(status: &SharedStatus, providers: &[ProviderEntry]) -> PersistedState {
+    let st = status.read();
+    let mut out = PersistedState {
+        current_ip: st.current_ip.clone(),
+        current_ipv6: st.current_ipv6.clone(),
+        ..Default::default()
+    };
+    for e in providers {
+        let stat = st.providers.get(&e.key).cloned().unwrap_or_default();
+        out.providers.insert(
+            e.key.clone(),
+            ProviderState {
+                target: target_of(&*e.prov),
+                last_ok: stat.last_ok,
+                last_ip: stat.last_ip,
+                last_err: stat.last_err,
+                cache: e.prov.cache(),
+            },
+        );
+    }
+    out
+}
+
+/// `true` when `ip` was already pushed successfully and the last push is
+/// younger than `refresh` seconds (`0` disables change detection).
+fn is_fresh(status: &SharedStatus, key: &str, ip: &str, refresh: u64) -> bool {
+    if refresh == 0 {
+        return false;
+    }
+    let st = status.read();
+    let Some(ent) = st.providers.get(key) else {
+        return false;
+    };
+    match (&ent.last_ip, ent.last_ok) {
+        (Some(last), Some(t)) if last == ip => {
+            Utc::now().signed_duration_since(t).num_seconds() < refresh as i64
+        }
+        _ => false,
+    }
 }
 
-/*──────── status helper ────────*/
-fn set_stat(status: &SharedStatus, key: &str, ok: Option<DateTime<Utc>>, err: Option<String>) {
+fn set_stat(
+    status: &SharedStatus,
+    key: &str,
+    ok: Option<(DateTime<Utc>, &str)>,
+    err: Option<String>,
+) {
     let mut st = status.write();
     let ent = st.providers.entry(key.to_owned()).or_default();
-    if let Some(t) = ok {
+    if let Some((t, ip)) = ok {
         ent.last_ok = Some(t);
+        ent.last_ip = Some(ip.to_owned());
+        ent.live_value = Some(ip.to_owned());
         ent.last_err = None;
     }
     if let Some(e) = err {
         ent.last_err = Some(e);
     }
 }
+
+/*──────── local tests (memory provider, POSIX `sh` detector) ────────*/
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::cfg::{DetectCfg, HttpCfg, SchedulerCfg};
+    use ddns_provider::ProviderTable;
+    use ddns_provider_memory::{MemoryFactory, MemoryProvider};
+    use tokio::sync::broadcast;
+
+    fn config(params: ProviderTable) -> AppConfig {
+        AppConfig {
+            http: HttpCfg::default(),
+            scheduler: SchedulerCfg::default(),
+            state: None,
+            detect: vec![DetectCfg::Command {
+                cmd: "echo 203.0.113.7".into(),
+                timeout: None,
+                priority: None,
+                family: None,
+            }],
+            provider: vec![ProviderCfg {
+                kind: "memory".into(),
+                zone: "example.test".into(),
+                record: "home".into(),
+                alias: None,
+                record_type: "A".into(),
+                ttl: 60,
+                value: None,
+                params,
+            }],
+        }
+    }
+
+    async fn cycle(cfg: &AppConfig, providers: &[ProviderEntry], status: &SharedStatus) {
+        let (bus, _) = broadcast::channel(64);
+        let sem = Arc::new(Semaphore::new(1));
+        one_cycle(cfg, providers, sem, status.clone(), bus, None)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn live_values_compare_normalised() {
+        use RecordType::*;
+        assert!(same_value(AAAA, "2001:DB8::1", "2001:db8:0::1"));
+        assert!(same_value(CNAME, "Target.Example.", "target.example"));
+        assert!(same_value(TXT, "\"v=spf1 -all\"", "v=spf1 -all"));
+        assert!(same_value(
+            HTTPS,
+            "1 . alpn=\"h2,h3\" port=\"8443\"",
+            "1  .  alpn=h2,h3 port=8443"
+        ));
+        assert!(!same_value(HTTPS, "1 . alpn=\"h2\"", "1 . alpn=h3"));
+        assert!(!same_value(SVCB, "1 . alpn=h2", "2 . alpn=h2"));
+    }
+
+    #[tokio::test]
+    async fn pushes_changed_ip_only() {
+        let cfg = config(ProviderTable::new());
+        let mem = Arc::new(MemoryProvider::new("example.test", "home", "A", 60).unwrap());
+        let providers = vec![ProviderEntry {
+            key: display_key(&cfg.provider[0]),
+            prov: mem.clone(),
+            ttl: 60,
+            value: None,
+        }];
+        let status = SharedStatus::default();
+
+        cycle(&cfg, &providers, &status).await;
+        let live = mem.get_record("example.test", "home", RecordType::A).await;
+        assert_eq!(live.unwrap().as_deref(), Some("203.0.113.7"));
+        let st = status.read().providers[&providers[0].key].clone();
+        assert_eq!(st.last_ip.as_deref(), Some("203.0.113.7"));
+        assert_eq!(mem.writes(), 1);
+
+        // unchanged address: the provider is not contacted again
+        let calls = mem.calls();
+        cycle(&cfg, &providers, &status).await;
+        assert_eq!(mem.calls(), calls);
+    }
+
+    #[tokio::test]
+    async fn permanent_failure_is_not_retried() {
+        let mut params = ProviderTable::new();
+        params.insert("fail_every".into(), 2.into());
+        params.insert("fail_with".into(), "auth".into());
+        let cfg = config(params);
+        let mut registry = ProviderRegistry::empty();
+        registry.register(MemoryFactory);
+        let status = SharedStatus::default();
+        let providers = init_providers(&cfg, &status, None, &registry).unwrap();
+
+        // call 1 (read) succeeds, call 2 (write) fails with `auth` and gives up
+        cycle(&cfg, &providers, &status).await;
+        let st = status.read().providers[&providers[0].key].clone();
+        assert!(st.last_ok.is_none());
+        assert!(st.last_err.unwrap().contains("injected"));
+    }
+
+    #[tokio::test]
+    async fn long_retry_after_defers_to_next_cycle() {
+        let mut params = ProviderTable::new();
+        params.insert("fail_every".into(), 1.into());
+        params.insert("fail_with".into(), "rate_limited".into());
+        params.insert("retry_after".into(), 1800.into());
+        let cfg = config(params);
+        let mut registry = ProviderRegistry::empty();
+        registry.register(MemoryFactory);
+        let status = SharedStatus::default();
+        let providers = init_providers(&cfg, &status, None, &registry).unwrap();
+
+        // a 30-minute Retry-After is not waited for inside the cycle
+        tokio::time::timeout(Duration::from_secs(5), cycle(&cfg, &providers, &status))
+            .await
+            .expect("cycle must not wait for the Retry-After");
+        let st = status.read().providers[&providers[0].key].clone();
+        assert!(st.last_ok.is_none());
+        assert!(st.last_err.unwrap().contains("next cycle"));
+    }
+}
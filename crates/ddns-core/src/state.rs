@@ -0,0 +1,110 @@
+//! Optional on-disk state (`[state] path = ...`)
+//!
+//! * Keeps last-pushed IPs, dashboard history and provider ID caches across restarts.
+//! * Format follows the file extension: `.toml` → TOML, anything else → JSON.
+//! * Written atomically (temp file + rename) after every cycle.
+
+use crate::{cfg::StateCfg, status::SharedStatus};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use ddns_provider::{ProviderCache, file::write_atomic};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+/*──────── file layout ────────*/
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PersistedState {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_ip: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_ipv6: Option<String>,
+    #[serde(default)]
+    pub providers: BTreeMap<String, ProviderState>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderState {
+    /// `record.zone/TYPE` the cache belongs to; a mismatch discards it
+    #[serde(default)]
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_ok: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_ip: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_err: Option<String>,
+    /// provider-specific identifiers (zone id, record id, …)
+    #[serde(default)]
+    pub cache: ProviderCache,
+}
+
+/*──────── store ────────*/
+pub struct StateStore {
+    path: PathBuf,
+    loaded: PersistedState,
+}
+
+impl StateStore {
+    /// Open the state file; a missing file yields an empty state.
+    pub fn open(cfg: &StateCfg) -> Result<Self> {
+        let path = PathBuf::from(&cfg.path);
+        let loaded = if path.exists() {
+            let raw = fs::read_to_string(&path)
+                .with_context(|| format!("read state file `{}`", path.display()))?;
+            let st: PersistedState = if is_toml(&path) {
+                toml::from_str(&raw)?
+            } else {
+                serde_json::from_str(&raw)?
+            };
+            info!("state loaded from `{}`", path.display());
+            st
+        } else {
+            info!("state file `{}` not found; starting fresh", path.display());
+            PersistedState::default()
+        };
+        Ok(Self { path, loaded })
+    }
+
+    /// Copy persisted dashboard history into the shared status.
+    pub fn seed(&self, status: &SharedStatus) {
+        let mut st = status.write();
+        st.current_ip = self.loaded.current_ip.clone();
+        st.current_ipv6 = self.loaded.current_ipv6.clone();
+        for (key, p) in &self.loaded.providers {
+            let ent = st.providers.entry(key.clone()).or_default();
+            ent.last_ok = p.last_ok;
+            ent.last_ip = p.last_ip.clone();
+            ent.last_err = p.last_err.clone();
+        }
+    }
+
+    /// Cached identifiers for `key`, only if they were stored for `target`.
+    pub fn cache_for(&self, key: &str, target: &str) -> Option<&ProviderCache> {
+        self.loaded
+            .providers
+            .get(key)
+            .filter(|p| p.target == target)
+            .map(|p| &p.cache)
+    }
+
+    /// Write `state` atomically (`<path>.tmp`, then rename).
+    pub fn save(&self, state: &PersistedState) -> Result<()> {
+        let body = if is_toml(&self.path) {
+            toml::to_string_pretty(state)?
+        } else {
+            serde_json::to_string_pretty(state)?
+        };
+        write_atomic(&self.path, &body, false)
+            .with_context(|| format!("write `{}`", self.path.display()))
+    }
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("toml"))
+}
@@ -9,6 +9,10 @@ use tokio::sync::broadcast;
 #[derive(Clone, Serialize, Default)]
 pub struct ProviderStat {
     pub last_ok: Option<DateTime<Utc>>,
+    /// value of the last successful push; used for change detection
+    pub last_ip: Option<String>,
+    /// value the record held at the last read (or write)
+    pub live_value: Option<String>,
     pub last_err: Option<String>,
 }
 
@@ -16,7 +20,10 @@ pub struct ProviderStat {
 pub struct AppStatus {
     pub now: DateTime<Utc>,
     pub next_tick: Option<DateTime<Utc>>,
+    /// last detected IPv4 address
     pub current_ip: Option<String>,
+    /// last detected IPv6 address
+    pub current_ipv6: Option<String>,
     pub providers: HashMap<String, ProviderStat>,
 }
 
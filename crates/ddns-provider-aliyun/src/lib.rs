@@ -1,8 +1,9 @@
 //! Aliyun DNS provider – production-ready
 //!
-//! * Supports `A` / `AAAA` record *upsert* (create if absent, update if present).  
+//! * Supports `A` / `AAAA` / `TXT` / `CNAME` / `HTTPS` / `SVCB` record *upsert* (create if absent, update if present).  
 //! * Auth via **AccessKey / AccessSecret** – a RAM sub-account with “Read / Write DNS” is enough.  
-//! * All API errors are mapped to [`ddns_provider::ProviderError`].  
+//! * All API errors are mapped to [`ddns_provider::ProviderError`] by their `Code`.  
+//! * `DomainRecordDuplicate` on update (value unchanged) counts as success.  
 //! * `zone_id`  is cached via `DescribeDomainInfo`.  
 //! * `record_id` is cached via `DescribeSubDomainRecords`.
 
@@ -11,14 +12,19 @@
 use async_trait::async_trait;
 use base64::{Engine as _, engine::general_purpose::STANDARD as B64};
 use chrono::Utc;
-use ddns_provider::{DnsProvider, ProviderError, RecordType};
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderCache, ProviderError, ProviderFactory, ProviderSpec,
+    ProviderTable, RecordType, name::fqdn, parse_table,
+};
 use hmac::{Hmac, Mac};
 use once_cell::sync::OnceCell;
 use percent_encoding::{AsciiSet, CONTROLS, percent_encode};
 use reqwest::{Client, Response, StatusCode};
+use serde::Deserialize;
 use serde_json::Value;
 use sha1::Sha1;
-use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::{collections::BTreeMap, time::Duration};
 use tracing::{debug, info};
 
 type HmacSha1 = Hmac<Sha1>;
@@ -53,6 +59,43 @@ fn encode(v: &str) -> String {
     percent_encode(v.as_bytes(), SAFE).to_string()
 }
 
+/// Error code returned when an update would not change anything.
+const DUPLICATE: &str = "DomainRecordDuplicate";
+
+/// Map an Aliyun error `Code` to a [`ProviderError`]; the code is kept as
+/// message prefix (`Code: Message`).
+fn map_error(
+    status: StatusCode,
+    code: &str,
+    msg: &str,
+    retry_after: Option<Duration>,
+) -> ProviderError {
+    let text = format!("{code}: {msg}");
+    match code {
+        "InvalidAccessKeyId.NotFound"
+        | "InvalidAccessKeyId.Inactive"
+        | "SignatureDoesNotMatch"
+        | "IncompleteSignature"
+        | "Forbidden"
+        | "Forbidden.RAM"
+        | "IncorrectDomainUser" => ProviderError::Auth(text),
+        "InvalidDomainName.NoExist" | "DomainRecordNotBelongToUser" | "InvalidRR.NoExist" => {
+            ProviderError::NotFound(text)
+        }
+        c if c.starts_with("Throttling") => ProviderError::RateLimited { retry_after },
+        "ServiceUnavailable" | "InternalError" | "UnknownError" => ProviderError::Transient(text),
+        c if c == DUPLICATE
+            || c.starts_with("InvalidParameter")
+            || c.starts_with("MissingParameter")
+            || c.ends_with(".Format")
+            || c.contains("Invalid") =>
+        {
+            ProviderError::InvalidInput(text)
+        }
+        _ => ProviderError::from_status(status, text, retry_after),
+    }
+}
+
 /*──────── provider struct ────────*/
 
 pub struct AliProvider {
@@ -66,7 +109,7 @@ pub struct AliProvider {
     client: Client,
 
     zone_id: OnceCell<String>,
-    record_id: OnceCell<String>,
+    record_id: RwLock<Option<String>>,
 }
 
 impl AliProvider {
@@ -83,18 +126,14 @@ impl AliProvider {
         Ok(Self {
             zone_name: zone.to_owned(),
             record_name: record.to_owned(),
-            rtype: if record_type.eq_ignore_ascii_case("AAAA") {
-                RecordType::AAAA
-            } else {
-                RecordType::A
-            },
+            rtype: record_type.parse()?,
             ttl,
             ak: access_key.to_owned(),
             sk: access_sec.to_owned(),
             region: region.to_owned(),
             client: Client::new(),
             zone_id: OnceCell::new(),
-            record_id: OnceCell::new(),
+            record_id: RwLock::new(None),
         })
     }
 
@@ -136,14 +175,15 @@ impl AliProvider {
 
         let resp: Response = self.client.get(url).send().await?;
         let status = resp.status();
-        let v: Value = resp.json().await?;
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        let v: Value = resp.json().await.unwrap_or(Value::Null);
 
         if status == StatusCode::OK {
             Ok(v)
         } else {
-            Err(ProviderError::Api(
-                v["Message"].as_str().unwrap_or("Aliyun error").to_string(),
-            ))
+            let code = v["Code"].as_str().unwrap_or_default();
+            let msg = v["Message"].as_str().unwrap_or("Aliyun error");
+            Err(map_error(status, code, msg, retry_after))
         }
     }
 
@@ -160,35 +200,54 @@ impl AliProvider {
         let v = self.call(p).await?;
         let id = v["DomainId"]
             .as_str()
-            .ok_or_else(|| ProviderError::Api("zone not found".into()))?;
+            .ok_or_else(|| ProviderError::NotFound(format!("zone {}", self.zone_name)))?;
         let _ = self.zone_id.set(id.to_owned());
         Ok(self.zone_id.get().expect("zone_id set"))
     }
 
-    async fn ensure_record_id(&self) -> Result<Option<&str>, ProviderError> {
-        if let Some(id) = self.record_id.get() {
+    fn cached_record_id(&self) -> Option<String> {
+        self.record_id.read().expect("record_id lock").clone()
+    }
+
+    fn set_record_id(&self, id: Option<String>) {
+        *self.record_id.write().expect("record_id lock") = id;
+    }
+
+    async fn ensure_record_id(&self) -> Result<Option<String>, ProviderError> {
+        if let Some(id) = self.cached_record_id() {
             return Ok(Some(id));
         }
+        self.lookup_record(&self.zone_name, &self.record_name, self.rtype)
+            .await?;
+        Ok(self.cached_record_id())
+    }
+
+    /// Fetch the record by sub-domain/type; caches its id when it is the
+    /// configured one.
+    async fn lookup_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<Value>, ProviderError> {
         let mut p = BTreeMap::new();
         p.insert("Action".into(), "DescribeSubDomainRecords".into());
-        p.insert(
-            "SubDomain".into(),
-            format!("{}.{}", self.record_name, self.zone_name),
-        );
+        p.insert("SubDomain".into(), fqdn(name, zone));
+        p.insert("Type".into(), typ.as_str().into());
         let v = self.call(p).await?;
-        if let Some(id) = v["DomainRecords"]["Record"][0]["RecordId"].as_str() {
-            let _ = self.record_id.set(id.to_owned());
-            Ok(Some(self.record_id.get().unwrap()))
-        } else {
-            Ok(None)
+        let rec = v["DomainRecords"]["Record"].get(0).cloned();
+        if self.is_configured(zone, name, typ) {
+            self.set_record_id(
+                rec.as_ref()
+                    .and_then(|r| r["RecordId"].as_str())
+                    .map(str::to_owned),
+            );
         }
+        Ok(rec)
     }
 
     fn rtype_str(&self) -> &'static str {
-        match self.rtype {
-            RecordType::A => "A",
-            RecordType::AAAA => "AAAA",
-        }
+        self.rtype.as_str()
     }
 
     /*──────── create / update helpers ────────*/
@@ -206,7 +265,7 @@ impl AliProvider {
         let id = v["RecordId"]
             .as_str()
             .ok_or_else(|| ProviderError::Api("add: missing RecordId".into()))?;
-        let _ = self.record_id.set(id.to_owned());
+        self.set_record_id(Some(id.to_owned()));
         info!("Aliyun created record id={id}");
         Ok(())
     }
@@ -219,12 +278,58 @@ impl AliProvider {
         p.insert("Type".into(), self.rtype_str().into());
         p.insert("Value".into(), ip.into());
         p.insert("TTL".into(), self.ttl.to_string());
-        self.call(p).await?;
-        info!("Aliyun updated record id={rid}");
+        match self.call(p).await {
+            Ok(_) => info!("Aliyun updated record id={rid}"),
+            Err(ProviderError::InvalidInput(m)) if m.starts_with(DUPLICATE) => {
+                debug!("Aliyun record id={rid} already holds {ip}")
+            }
+            Err(e) => return Err(e),
+        }
         Ok(())
     }
 }
 
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct AliCfg {
+    access_key: String,
+    access_secret: String,
+    #[serde(default = "default_region")]
+    region: String,
+}
+
+fn default_region() -> String {
+    "cn-hangzhou".into()
+}
+
+/// Builds `kind = "aliyun"` entries; expects `access_key`, `access_secret`
+/// and optionally `region` (default `cn-hangzhou`).
+pub struct AliFactory;
+
+impl ProviderFactory for AliFactory {
+    fn kind(&self) -> &'static str {
+        "aliyun"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: AliCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(AliProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            spec.ttl,
+            &c.access_key,
+            &c.access_secret,
+            &c.region,
+        )?))
+    }
+}
+
 /*──────── DnsProvider impl ────────*/
 
 #[async_trait]
@@ -242,6 +347,100 @@ impl DnsProvider for AliProvider {
         self.rtype
     }
 
+    fn cache(&self) -> ProviderCache {
+        let mut c = ProviderCache::new();
+        if let Some(id) = self.zone_id.get() {
+            c.insert("zone_id".into(), id.clone());
+        }
+        if let Some(id) = self.cached_record_id() {
+            c.insert("record_id".into(), id);
+        }
+        c
+    }
+
+    fn restore_cache(&self, cache: &ProviderCache) {
+        if let Some(id) = cache.get("zone_id") {
+            let _ = self.zone_id.set(id.clone());
+        }
+        if let Some(id) = cache.get("record_id") {
+            self.set_record_id(Some(id.clone()));
+        }
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        Ok(self
+            .lookup_record(zone, name, typ)
+            .await?
+            .and_then(|r| r["Value"].as_str().map(str::to_owned)))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        const PAGE_SIZE: u64 = 500;
+        let mut out = Vec::new();
+        let mut page = 1;
+        loop {
+            let mut p = BTreeMap::new();
+            p.insert("Action".into(), "DescribeDomainRecords".into());
+            p.insert("DomainName".into(), zone.to_owned());
+            p.insert("PageNumber".into(), page.to_string());
+            p.insert("PageSize".into(), PAGE_SIZE.to_string());
+            let v = self.call(p).await?;
+            for r in v["DomainRecords"]["Record"]
+                .as_array()
+                .into_iter()
+                .flatten()
+            {
+                let Some(typ) = r["Type"].as_str().and_then(|t| t.parse().ok()) else {
+                    continue;
+                };
+                out.push(DnsRecord {
+                    id: r["RecordId"].as_str().unwrap_or_default().to_owned(),
+                    name: r["RR"].as_str().unwrap_or_default().to_owned(),
+                    typ,
+                    value: r["Value"].as_str().unwrap_or_default().to_owned(),
+                    ttl: r["TTL"].as_u64().unwrap_or_default() as u32,
+                });
+            }
+            let total = v["TotalCount"].as_u64().unwrap_or_default();
+            if page * PAGE_SIZE >= total {
+                break;
+            }
+            page += 1;
+        }
+        Ok(out)
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        let mut p = BTreeMap::new();
+        p.insert("Action".into(), "DeleteSubDomainRecords".into());
+        p.insert("DomainName".into(), zone.to_owned());
+        p.insert("RR".into(), name.to_owned());
+        p.insert("Type".into(), typ.to_string());
+        let v = self.call(p).await?;
+        let deleted = v["TotalCount"]
+            .as_u64()
+            .or_else(|| v["TotalCount"].as_str().and_then(|n| n.parse().ok()))
+            .unwrap_or_default();
+        if deleted == 0 {
+            return Err(ProviderError::NotFound(format!("{typ} {name}.{zone}")));
+        }
+        if zone == self.zone_name && name == self.record_name && typ == self.rtype {
+            self.set_record_id(None);
+        }
+        info!("Aliyun deleted {typ} {name}.{zone}");
+        Ok(())
+    }
+
     async fn upsert_record(
         &self,
         _zone: &str,
@@ -252,7 +451,13 @@ impl DnsProvider for AliProvider {
     ) -> Result<(), ProviderError> {
         let rid_opt = self.ensure_record_id().await?;
         match rid_opt {
-            Some(rid) => self.update_record(rid, ip).await,
+            Some(rid) => match self.update_record(&rid, ip).await {
+                Err(ProviderError::NotFound(_)) => {
+                    self.set_record_id(None);
+                    self.add_record(ip).await
+                }
+                res => res,
+            },
             None => self.add_record(ip).await,
         }?;
         debug!(
@@ -0,0 +1,808 @@
+//! Azure DNS provider
+//!
+//! * Supports `A` / `AAAA` / `TXT` / `CNAME` record sets through the Azure
+//!   Resource Manager DNS API (`PUT` of the whole record set).
+//! * Auth via **client credentials** (tenant / client id / secret of an app
+//!   registration with the *DNS Zone Contributor* role); the token is cached
+//!   until shortly before it expires.
+//! * Writes carry the record set's `etag` in `If-Match` (`If-None-Match: *`
+//!   when creating); a conflicting change (`412`) drops the cached etag and is
+//!   retried after a fresh read.
+//! * Without `resource_group` the zone is looked up in the subscription and
+//!   its resource path cached.
+
+use async_trait::async_trait;
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderCache, ProviderError, ProviderFactory, ProviderSpec,
+    ProviderTable, RecordType, parse_table,
+};
+use once_cell::sync::OnceCell;
+use reqwest::{
+    Client, Method, StatusCode,
+    header::{HeaderName, IF_MATCH, IF_NONE_MATCH},
+};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+use tracing::{debug, info};
+
+const LOGIN_ROOT: &str = "https://login.microsoftonline.com";
+const ARM_ROOT: &str = "https://management.azure.com";
+const SCOPE: &str = "https://management.azure.com/.default";
+const API_VERSION: &str = "2018-05-01";
+const DEFAULT_TTL: u32 = 300;
+/// refresh this long before the token runs out
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// Map an ARM error `code` to a [`ProviderError`].
+fn map_error(
+    status: StatusCode,
+    code: &str,
+    msg: &str,
+    retry_after: Option<Duration>,
+) -> ProviderError {
+    let text = format!("{code}: {msg}");
+    match code {
+        "AuthenticationFailed"
+        | "InvalidAuthenticationToken"
+        | "ExpiredAuthenticationToken"
+        | "AuthorizationFailed"
+        | "LinkedAuthorizationFailed" => ProviderError::Auth(text),
+        "NotFound"
+        | "ResourceNotFound"
+        | "ParentResourceNotFound"
+        | "ResourceGroupNotFound"
+        | "SubscriptionNotFound" => ProviderError::NotFound(text),
+        "TooManyRequests" | "SubscriptionRequestsThrottled" => {
+            ProviderError::RateLimited { retry_after }
+        }
+        // the record set changed (or appeared) since we read it
+        "PreconditionFailed" => ProviderError::Transient(text),
+        "BadRequest" | "InvalidRequestFormat" | "InvalidRequestContent" => {
+            ProviderError::InvalidInput(text)
+        }
+        _ if status == StatusCode::PRECONDITION_FAILED => ProviderError::Transient(text),
+        _ => ProviderError::from_status(status, text, retry_after),
+    }
+}
+
+/*──────── record-set JSON ────────*/
+
+/// Azure DNS stores TXT data as strings of at most 255 characters.
+fn txt_chunks(value: &str) -> Vec<String> {
+    let chars: Vec<char> = value.trim_matches('"').chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars.chunks(255).map(|c| c.iter().collect()).collect()
+}
+
+/// `properties` of a record set holding the single value `value`.
+fn record_props(typ: RecordType, value: &str, ttl: u32) -> Result<Value, ProviderError> {
+    let mut p = json!({ "TTL": ttl });
+    match typ {
+        RecordType::A => p["ARecords"] = json!([{ "ipv4Address": value }]),
+        RecordType::AAAA => p["AAAARecords"] = json!([{ "ipv6Address": value }]),
+        RecordType::TXT => p["TXTRecords"] = json!([{ "value": txt_chunks(value) }]),
+        RecordType::CNAME => p["CNAMERecord"] = json!({ "cname": value }),
+        RecordType::HTTPS | RecordType::SVCB => {
+            return Err(ProviderError::Unsupported(
+                "Azure DNS has no HTTPS / SVCB records",
+            ));
+        }
+    }
+    Ok(p)
+}
+
+/// Values held by the `properties` of a record set.
+fn record_values(typ: RecordType, props: &Value) -> Vec<String> {
+    let (list, field) = match typ {
+        RecordType::A => ("ARecords", "ipv4Address"),
+        RecordType::AAAA => ("AAAARecords", "ipv6Address"),
+        RecordType::CNAME => {
+            return props["CNAMERecord"]["cname"]
+                .as_str()
+                .map(|c| vec![c.to_owned()])
+                .unwrap_or_default();
+        }
+        RecordType::TXT => {
+            return props["TXTRecords"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|r| {
+                    r["value"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_str)
+                        .collect()
+                })
+                .collect();
+        }
+        RecordType::HTTPS | RecordType::SVCB => return Vec::new(),
+    };
+    props[list]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|r| r[field].as_str().map(str::to_owned))
+        .collect()
+}
+
+/// What a write has to carry over from the live record set.
+#[derive(Clone)]
+struct LiveSet {
+    etag: String,
+    metadata: Option<Value>,
+}
+
+impl LiveSet {
+    fn from_reply(v: &Value) -> Option<Self> {
+        Some(Self {
+            etag: v["etag"].as_str()?.to_owned(),
+            metadata: v["properties"]
+                .get("metadata")
+                .filter(|m| !m.is_null())
+                .cloned(),
+        })
+    }
+}
+
+/*──────── provider struct ────────*/
+
+pub struct AzureProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    tenant_id: String,
+    client_id: String,
+    client_secret: String,
+    subscription_id: String,
+    resource_group: Option<String>,
+    client: Client,
+    login_root: String,
+    arm_root: String,
+
+    token: RwLock<Option<(String, Instant)>>,
+    /// `/subscriptions/…/dnsZones/<zone>` of the configured zone
+    zone_path: OnceCell<String>,
+    live: RwLock<Option<LiveSet>>,
+}
+
+impl AzureProvider {
+    /// `resource_group` is optional; without it the zone is searched in
+    /// the whole subscription.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        zone: &str,
+        record: &str,
+        record_type: &str,
+        tenant_id: &str,
+        client_id: &str,
+        client_secret: &str,
+        subscription_id: &str,
+        resource_group: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let rtype: RecordType = record_type.parse()?;
+        if matches!(rtype, RecordType::HTTPS | RecordType::SVCB) {
+            anyhow::bail!("azure: Azure DNS has no {rtype} records");
+        }
+        Ok(Self {
+            zone_name: zone.trim_end_matches('.').to_owned(),
+            record_name: record.to_owned(),
+            rtype,
+            tenant_id: tenant_id.to_owned(),
+            client_id: client_id.to_owned(),
+            client_secret: client_secret.to_owned(),
+            subscription_id: subscription_id.to_owned(),
+            resource_group: resource_group.map(str::to_owned),
+            client: Client::new(),
+            login_root: LOGIN_ROOT.into(),
+            arm_root: ARM_ROOT.into(),
+            token: RwLock::new(None),
+            zone_path: OnceCell::new(),
+            live: RwLock::new(None),
+        })
+    }
+
+    /*──────── OAuth token ────────*/
+
+    /// Client-credentials access token for ARM, cached until it expires.
+    async fn access_token(&self) -> Result<String, ProviderError> {
+        if let Some((tok, until)) = &*self.token.read().expect("token lock")
+            && Instant::now() < *until
+        {
+            return Ok(tok.clone());
+        }
+        let resp = self
+            .client
+            .post(format!(
+                "{}/{}/oauth2/v2.0/token",
+                self.login_root, self.tenant_id
+            ))
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+                ("scope", SCOPE),
+            ])
+            .send()
+            .await?;
+        let status = resp.status();
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        let v: Value = resp.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            let msg = format!(
+                "token request: {} {}",
+                v["error"].as_str().unwrap_or("HTTP"),
+                v["error_description"]
+                    .as_str()
+                    .and_then(|d| d.lines().next())
+                    .map(str::to_owned)
+                    .unwrap_or_else(|| status.to_string())
+            );
+            // `invalid_client` / unknown tenant come back as 400 / 401
+            return Err(match status.as_u16() {
+                429 => ProviderError::RateLimited { retry_after },
+                500..=599 => ProviderError::Transient(msg),
+                _ => ProviderError::Auth(msg),
+            });
+        }
+        let tok = v["access_token"]
+            .as_str()
+            .ok_or_else(|| ProviderError::Api("token request: missing access_token".into()))?
+            .to_owned();
+        let lifetime = Duration::from_secs(v["expires_in"].as_u64().unwrap_or(3600));
+        let until = Instant::now() + lifetime.saturating_sub(EXPIRY_MARGIN);
+        *self.token.write().expect("token lock") = Some((tok.clone(), until));
+        Ok(tok)
+    }
+
+    /*──────── ARM request helper ────────*/
+
+    /// `url` is a path below the ARM root, which gets the `api-version`
+    /// appended, or absolute (ARM `nextLink`s).
+    async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        headers: &[(HeaderName, &str)],
+        body: Option<Value>,
+    ) -> Result<Value, ProviderError> {
+        let url = if url.starts_with('/') {
+            let sep = if url.contains('?') { '&' } else { '?' };
+            format!("{}{url}{sep}api-version={API_VERSION}", self.arm_root)
+        } else {
+            url.to_owned()
+        };
+        let mut req = self
+            .client
+            .request(method, url)
+            .bearer_auth(self.access_token().await?);
+        for (k, v) in headers {
+            req = req.header(k, *v);
+        }
+        if let Some(b) = body {
+            req = req.json(&b);
+        }
+        let resp = req.send().await?;
+        let status = resp.status();
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        // DELETE answers with an empty body; gateways not always with JSON
+        let v: Value = resp.json().await.unwrap_or(Value::Null);
+        if status.is_success() {
+            return Ok(v);
+        }
+        // record-set errors are not always wrapped in `error`
+        let err = if v["error"].is_object() {
+            &v["error"]
+        } else {
+            &v
+        };
+        let code = err["code"].as_str().unwrap_or("HTTP");
+        let msg = err["message"]
+            .as_str()
+            .map(str::to_owned)
+            .unwrap_or_else(|| status.to_string());
+        Err(map_error(status, code, &msg, retry_after))
+    }
+
+    /*──────── zone / record helpers ────────*/
+
+    /// Resource path of `zone`; only the configured zone is cached.
+    async fn zone_path_for(&self, zone: &str) -> Result<String, ProviderError> {
+        let zone = zone.trim_end_matches('.');
+        let own = zone == self.zone_name;
+        if own && let Some(p) = self.zone_path.get() {
+            return Ok(p.clone());
+        }
+        let path = match &self.resource_group {
+            Some(rg) => format!(
+                "/subscriptions/{}/resourceGroups/{rg}/providers/Microsoft.Network/dnsZones/{zone}",
+                self.subscription_id
+            ),
+            None => self.find_zone(zone).await?,
+        };
+        if own {
+            let _ = self.zone_path.set(path.clone());
+        }
+        Ok(path)
+    }
+
+    /// Search the subscription's DNS zones for `zone`.
+    async fn find_zone(&self, zone: &str) -> Result<String, ProviderError> {
+        let mut url = format!(
+            "/subscriptions/{}/providers/Microsoft.Network/dnszones",
+            self.subscription_id
+        );
+        loop {
+            let v = self.send(Method::GET, &url, &[], None).await?;
+            let hit = v["value"].as_array().into_iter().flatten().find(|z| {
+                z["name"]
+                    .as_str()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(zone))
+            });
+            if let Some(id) = hit.and_then(|z| z["id"].as_str()) {
+                return Ok(id.to_owned());
+            }
+            match v["nextLink"].as_str() {
+                Some(next) => url = next.to_owned(),
+                None => {
+                    return Err(ProviderError::NotFound(format!(
+                        "DNS zone {zone} in subscription {}",
+                        self.subscription_id
+                    )));
+                }
+            }
+        }
+    }
+
+    fn set_path(zone_path: &str, name: &str, typ: RecordType) -> String {
+        let name = if name.is_empty() { "@" } else { name };
+        format!("{zone_path}/{typ}/{name}")
+    }
+
+    fn cached_live(&self) -> Option<LiveSet> {
+        self.live.read().expect("live lock").clone()
+    }
+
+    fn set_live(&self, live: Option<LiveSet>) {
+        *self.live.write().expect("live lock") = live;
+    }
+
+    /// The record set at `path`, `None` when it does not exist.
+    async fn fetch_set(&self, path: &str) -> Result<Option<Value>, ProviderError> {
+        match self.send(Method::GET, path, &[], None).await {
+            Ok(v) => Ok(Some(v)),
+            // a missing record set, not a missing zone / resource group
+            Err(ProviderError::NotFound(m)) if m.starts_with("NotFound:") => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Fetch the configured record set; refreshes the cached etag.
+    async fn lookup_record(&self) -> Result<Option<Value>, ProviderError> {
+        let zp = self.zone_path_for(&self.zone_name).await?;
+        let set = self
+            .fetch_set(&Self::set_path(&zp, &self.record_name, self.rtype))
+            .await?;
+        self.set_live(set.as_ref().and_then(LiveSet::from_reply));
+        Ok(set)
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct AzureCfg {
+    tenant_id: String,
+    client_id: String,
+    client_secret: String,
+    subscription_id: String,
+    #[serde(default)]
+    resource_group: Option<String>,
+}
+
+/// Builds `kind = "azure"` entries; expects `tenant_id`, `client_id`,
+/// `client_secret`, `subscription_id` and optionally `resource_group`.
+pub struct AzureFactory;
+
+impl ProviderFactory for AzureFactory {
+    fn kind(&self) -> &'static str {
+        "azure"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: AzureCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(AzureProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            &c.tenant_id,
+            &c.client_id,
+            &c.client_secret,
+            &c.subscription_id,
+            c.resource_group.as_deref(),
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for AzureProvider {
+    fn name(&self) -> &'static str {
+        "Azure"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    fn cache(&self) -> ProviderCache {
+        let mut c = ProviderCache::new();
+        if let Some(p) = self.zone_path.get() {
+            c.insert("zone_path".into(), p.clone());
+        }
+        c
+    }
+
+    fn restore_cache(&self, cache: &ProviderCache) {
+        if let Some(p) = cache.get("zone_path") {
+            let _ = self.zone_path.set(p.clone());
+        }
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        let set = if zone.trim_end_matches('.') == self.zone_name
+            && name == self.record_name
+            && typ == self.rtype
+        {
+            self.lookup_record().await?
+        } else {
+            let zp = self.zone_path_for(zone).await?;
+            self.fetch_set(&Self::set_path(&zp, name, typ)).await?
+        };
+        Ok(set.and_then(|s| record_values(typ, &s["properties"]).into_iter().next()))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        let zp = self.zone_path_for(zone).await?;
+        let mut out = Vec::new();
+        let mut url = format!("{zp}/recordsets?$top=500");
+        loop {
+            let v = self.send(Method::GET, &url, &[], None).await?;
+            for s in v["value"].as_array().into_iter().flatten() {
+                // `Microsoft.Network/dnszones/A`
+                let Some(typ) = s["type"]
+                    .as_str()
+                    .and_then(|t| t.rsplit('/').next())
+                    .and_then(|t| t.parse::<RecordType>().ok())
+                else {
+                    continue;
+                };
+                let props = &s["properties"];
+                for value in record_values(typ, props) {
+                    out.push(DnsRecord {
+                        id: s["id"].as_str().unwrap_or_default().to_owned(),
+                        name: s["name"].as_str().unwrap_or("@").to_owned(),
+                        typ,
+                        value,
+                        ttl: props["TTL"].as_u64().unwrap_or_default() as u32,
+                    });
+                }
+            }
+            match v["nextLink"].as_str() {
+                Some(next) => url = next.to_owned(),
+                None => break,
+            }
+        }
+        Ok(out)
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        let zp = self.zone_path_for(zone).await?;
+        let path = Self::set_path(&zp, name, typ);
+        let set = self
+            .fetch_set(&path)
+            .await?
+            .ok_or_else(|| ProviderError::NotFound(format!("{typ} {name}.{zone}")))?;
+        let etag = set["etag"].as_str().unwrap_or("*");
+        self.send(Method::DELETE, &path, &[(IF_MATCH, etag)], None)
+            .await?;
+        if path == Self::set_path(&zp, &self.record_name, self.rtype) {
+            self.set_live(None);
+        }
+        info!("Azure deleted {typ} {name}.{zone}");
+        Ok(())
+    }
+
+    async fn upsert_record(
+        &self,
+        _zone: &str,
+        _name: &str,
+        _typ: RecordType,
+        ip: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        let zp = self.zone_path_for(&self.zone_name).await?;
+        let path = Self::set_path(&zp, &self.record_name, self.rtype);
+        let live = match self.cached_live() {
+            Some(l) => Some(l),
+            None => {
+                self.lookup_record().await?;
+                self.cached_live()
+            }
+        };
+
+        let ttl = if ttl == 0 { DEFAULT_TTL } else { ttl };
+        let mut props = record_props(self.rtype, ip, ttl)?;
+        // PUT replaces the whole set; keep its metadata (tags)
+        if let Some(m) = live.as_ref().and_then(|l| l.metadata.clone()) {
+            props["metadata"] = m;
+        }
+        let mut body = Map::new();
+        body.insert("properties".into(), props);
+        let pre = match &live {
+            Some(l) => (IF_MATCH, l.etag.as_str()),
+            None => (IF_NONE_MATCH, "*"),
+        };
+
+        match self
+            .send(Method::PUT, &path, &[pre], Some(Value::Object(body)))
+            .await
+        {
+            Ok(v) => {
+                self.set_live(LiveSet::from_reply(&v));
+                debug!("Azure upsert {} -> {ip}", self.record_name);
+                Ok(())
+            }
+            Err(e) => {
+                // stale etag or a racing writer: read again next time
+                self.set_live(None);
+                Err(e)
+            }
+        }
+    }
+}
+
+/*──────── tests (live one ignored by default) ────────*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    #[test]
+    fn error_codes() {
+        let map = |status, code| map_error(status, code, "m", None);
+        assert!(matches!(
+            map(StatusCode::UNAUTHORIZED, "ExpiredAuthenticationToken"),
+            ProviderError::Auth(_)
+        ));
+        assert!(matches!(
+            map(StatusCode::FORBIDDEN, "AuthorizationFailed"),
+            ProviderError::Auth(_)
+        ));
+        assert!(
+            matches!(map(StatusCode::NOT_FOUND, "ResourceGroupNotFound"), ProviderError::NotFound(m) if m == "ResourceGroupNotFound: m")
+        );
+        assert!(matches!(
+            map(
+                StatusCode::TOO_MANY_REQUESTS,
+                "SubscriptionRequestsThrottled"
+            ),
+            ProviderError::RateLimited { .. }
+        ));
+        assert!(matches!(
+            map(StatusCode::PRECONDITION_FAILED, "PreconditionFailed"),
+            ProviderError::Transient(_)
+        ));
+        assert!(matches!(
+            map(StatusCode::PRECONDITION_FAILED, "HTTP"),
+            ProviderError::Transient(_)
+        ));
+        assert!(matches!(
+            map(StatusCode::BAD_REQUEST, "InvalidRequestContent"),
+            ProviderError::InvalidInput(_)
+        ));
+        // unknown codes fall back to the status
+        assert!(matches!(
+            map(StatusCode::BAD_REQUEST, "SomethingNew"),
+            ProviderError::InvalidInput(_)
+        ));
+        assert!(matches!(
+            map(StatusCode::BAD_GATEWAY, "HTTP"),
+            ProviderError::Transient(_)
+        ));
+    }
+
+    #[test]
+    fn record_set_json() {
+        let long = "x".repeat(300);
+        let p = record_props(RecordType::TXT, &format!("\"{long}\""), 60).unwrap();
+        let chunks = p["TXTRecords"][0]["value"].as_array().unwrap();
+        assert_eq!((chunks.len(), chunks[0].as_str().unwrap().len()), (2, 255));
+        assert_eq!(record_values(RecordType::TXT, &p), [long]);
+
+        let p = record_props(RecordType::AAAA, "2001:db8::1", 60).unwrap();
+        assert_eq!(p["TTL"], 60);
+        assert_eq!(record_values(RecordType::AAAA, &p), ["2001:db8::1"]);
+        assert!(record_props(RecordType::HTTPS, "1 . alpn=h2", 60).is_err());
+    }
+
+    /// One request received by [`arm_server`].
+    struct Seen {
+        line: String,
+        headers: String,
+        body: String,
+    }
+
+    /// Stand-in for the login and ARM endpoints: answers each request with
+    /// the next scripted `(status, body)` and records what it received.
+    async fn arm_server(script: Vec<(u16, Value)>) -> (String, Arc<Mutex<Vec<Seen>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        tokio::spawn(async move {
+            for (status, reply) in script {
+                let (mut sock, _) = listener.accept().await.unwrap();
+                let mut req = Vec::new();
+                let mut buf = [0; 4096];
+                let (head, body) = loop {
+                    let n = sock.read(&mut buf).await.unwrap();
+                    req.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&req).into_owned();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let len = head
+                            .to_ascii_lowercase()
+                            .lines()
+                            .find_map(|l| {
+                                l.strip_prefix("content-length:")
+                                    .map(|v| v.trim().parse().unwrap())
+                            })
+                            .unwrap_or(0);
+                        if n == 0 || body.len() >= len {
+                            break (head.to_owned(), body.to_owned());
+                        }
+                    }
+                };
+                let (line, headers) = head.split_once("\r\n").unwrap_or((&head, ""));
+                log.lock().unwrap().push(Seen {
+                    line: line.to_owned(),
+                    headers: headers.to_ascii_lowercase(),
+                    body,
+                });
+                let body = reply.to_string();
+                let resp = format!(
+                    "HTTP/1.1 {status} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                sock.write_all(resp.as_bytes()).await.unwrap();
+            }
+        });
+        (url, seen)
+    }
+
+    #[tokio::test]
+    async fn etag_round_trips() {
+        let set = |etag: &str| json!({ "etag": etag, "properties": { "TTL": 300, "metadata": { "owner": "ops" } } });
+        let (url, seen) = arm_server(vec![
+            (200, json!({ "access_token": "tok", "expires_in": 3600 })),
+            // 1st upsert: no set yet → created with If-None-Match
+            (
+                404,
+                json!({ "error": { "code": "NotFound", "message": "no such record set" } }),
+            ),
+            (201, set("e1")),
+            // 2nd upsert: cached etag, but someone else changed the set
+            (
+                412,
+                json!({ "error": { "code": "PreconditionFailed", "message": "etag mismatch" } }),
+            ),
+            // 3rd upsert: read again, write with the fresh etag
+            (200, set("e3")),
+            (200, set("e4")),
+        ])
+        .await;
+        let mut az = AzureProvider::new(
+            "example.com",
+            "home",
+            "A",
+            "tenant",
+            "client",
+            "secret",
+            "sub",
+            Some("rg"),
+        )
+        .unwrap();
+        az.login_root = url.clone();
+        az.arm_root = url;
+        let upsert = || az.upsert_record("example.com", "home", RecordType::A, "192.0.2.1", 0);
+        let etag = |az: &AzureProvider| az.cached_live().map(|l| l.etag);
+
+        upsert().await.unwrap();
+        assert_eq!(etag(&az).as_deref(), Some("e1"));
+
+        let err = upsert().await.unwrap_err();
+        assert!(matches!(err, ProviderError::Transient(_)), "{err}");
+        assert_eq!(etag(&az), None);
+
+        upsert().await.unwrap();
+        assert_eq!(etag(&az).as_deref(), Some("e4"));
+
+        let seen = seen.lock().unwrap();
+        let lines: Vec<&str> = seen.iter().map(|r| r.line.as_str()).collect();
+        let set_path = "/subscriptions/sub/resourceGroups/rg/providers/Microsoft.Network/dnsZones/example.com/A/home?api-version=2018-05-01";
+        assert_eq!(
+            lines,
+            [
+                "POST /tenant/oauth2/v2.0/token HTTP/1.1".to_owned(),
+                format!("GET {set_path} HTTP/1.1"),
+                format!("PUT {set_path} HTTP/1.1"),
+                format!("PUT {set_path} HTTP/1.1"),
+                format!("GET {set_path} HTTP/1.1"),
+                format!("PUT {set_path} HTTP/1.1"),
+            ]
+        );
+        assert!(seen[2].headers.contains("if-none-match: *"));
+        assert!(seen[3].headers.contains("if-match: e1"));
+        assert!(seen[5].headers.contains("if-match: e3"));
+        assert!(seen[5].headers.contains("authorization: bearer tok"));
+        // PUT replaces the whole set: tags read back are written again
+        let body: Value = serde_json::from_str(&seen[5].body).unwrap();
+        assert_eq!(body["properties"]["metadata"], json!({ "owner": "ops" }));
+        assert_eq!(
+            body["properties"]["ARecords"],
+            json!([{ "ipv4Address": "192.0.2.1" }])
+        );
+        assert_eq!(body["properties"]["TTL"], DEFAULT_TTL);
+    }
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn live_upsert() {
+        let env = |k: &str| std::env::var(k).unwrap();
+        let az = AzureProvider::new(
+            "example.com",
+            "test-ddns",
+            "A",
+            &env("AZURE_TENANT_ID"),
+            &env("AZURE_CLIENT_ID"),
+            &env("AZURE_CLIENT_SECRET"),
+            &env("AZURE_SUBSCRIPTION_ID"),
+            None,
+        )
+        .unwrap();
+
+        az.upsert_record("example.com", "test-ddns", RecordType::A, "1.1.1.1", 60)
+            .await
+            .unwrap();
+    }
+}
@@ -1,18 +1,25 @@
 //! Cloudflare DNS provider – production-ready
 //!
-//! * Supports `A` / `AAAA` record *upsert* (create or update).  
+//! * Supports `A` / `AAAA` / `TXT` / `CNAME` / `HTTPS` / `SVCB` record *upsert* (create or update).  
 //! * Auth via **API Token** (recommended) – needs `Zone:Read` and `DNS:Edit`.  
 //! * `zone_id`  and `record_id` are cached locally to reduce API calls.  
-//! * All business errors are mapped to [`ddns_provider::ProviderError`].
+//! * All business errors are mapped to [`ddns_provider::ProviderError`] by
+//!   Cloudflare error code (auth / not-found / rate-limit / invalid input).  
+//! * A cached `record_id` that vanished upstream is dropped and the record re-created.
 
 use async_trait::async_trait;
-use ddns_provider::{DnsProvider, ProviderError, RecordType};
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderCache, ProviderError, ProviderFactory, ProviderSpec,
+    ProviderTable, RecordType, name::fqdn, parse_table,
+};
 use once_cell::sync::OnceCell;
 use reqwest::{
     Client, Response, StatusCode,
     header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue, USER_AGENT},
 };
+use serde::Deserialize;
 use serde_json::{Value, json};
+use std::sync::{Arc, RwLock};
 use tracing::{debug, info};
 
 const API_ROOT: &str = "https://api.cloudflare.com/client/v4";
@@ -27,7 +34,7 @@ pub struct CfProvider {
     client: Client,
 
     zone_id: OnceCell<String>,
-    record_id: OnceCell<String>,
+    record_id: RwLock<Option<String>>,
 }
 
 impl CfProvider {
@@ -49,15 +56,11 @@ impl CfProvider {
         Ok(Self {
             zone_name: zone.to_owned(),
             record_name: record.to_owned(),
-            rtype: if rtype.eq_ignore_ascii_case("AAAA") {
-                RecordType::AAAA
-            } else {
-                RecordType::A
-            },
+            rtype: rtype.parse()?,
             ttl,
             client: Client::builder().default_headers(hdr).build()?,
             zone_id: OnceCell::new(),
-            record_id: OnceCell::new(),
+            record_id: RwLock::new(None),
         })
     }
 
@@ -88,18 +91,38 @@ impl CfProvider {
         .await
     }
 
+    async fn delete(&self, path: &str) -> Result<Value, ProviderError> {
+        self.check(
+            self.client
+                .delete(format!("{API_ROOT}{path}"))
+                .send()
+                .await?,
+        )
+        .await
+    }
+
     async fn check(&self, resp: Response) -> Result<Value, ProviderError> {
         let status = resp.status();
-        let v: Value = resp.json().await?;
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        // throttled / gateway responses are not always JSON
+        let v: Value = resp.json().await.unwrap_or(Value::Null);
         if status == StatusCode::OK && v["success"].as_bool().unwrap_or(false) {
-            Ok(v)
-        } else {
-            let msg = v["errors"]
-                .get(0)
-                .and_then(|e| e["message"].as_str())
-                .unwrap_or("unknown error");
-            Err(ProviderError::Api(msg.to_owned()))
+            return Ok(v);
         }
+        let err = &v["errors"][0];
+        let msg = err["message"]
+            .as_str()
+            .unwrap_or("unknown error")
+            .to_owned();
+        Err(match err["code"].as_u64() {
+            Some(6003 | 6111 | 9103 | 9106 | 9107 | 9109 | 10000) => ProviderError::Auth(msg),
+            Some(7003 | 81044) => ProviderError::NotFound(msg),
+            Some(971) => ProviderError::RateLimited { retry_after },
+            Some(1004 | 9005 | 9007 | 9020 | 9021 | 81053 | 81057 | 81058) => {
+                ProviderError::InvalidInput(msg)
+            }
+            _ => ProviderError::from_status(status, msg, retry_after),
+        })
     }
 
     /*──────── zone / record helpers ────────*/
@@ -112,55 +135,113 @@ impl CfProvider {
         let id = v["result"]
             .get(0)
             .and_then(|r| r["id"].as_str())
-            .ok_or_else(|| ProviderError::Api("zone not found".into()))?;
+            .ok_or_else(|| ProviderError::NotFound(format!("zone {}", self.zone_name)))?;
         let _ = self.zone_id.set(id.to_owned());
         Ok(self.zone_id.get().expect("zone_id set"))
     }
 
-    async fn ensure_record_id(&self) -> Result<Option<&str>, ProviderError> {
-        if let Some(id) = self.record_id.get() {
+    /// Zone id for `zone`; the configured zone goes through the cache.
+    async fn zone_id_for(&self, zone: &str) -> Result<String, ProviderError> {
+        if zone == self.zone_name {
+            return self.ensure_zone_id().await.map(str::to_owned);
+        }
+        let v = self.get(&format!("/zones?name={zone}")).await?;
+        v["result"]
+            .get(0)
+            .and_then(|r| r["id"].as_str())
+            .map(str::to_owned)
+            .ok_or_else(|| ProviderError::NotFound(format!("zone {zone}")))
+    }
+
+    fn cached_record_id(&self) -> Option<String> {
+        self.record_id.read().expect("record_id lock").clone()
+    }
+
+    fn set_record_id(&self, id: Option<String>) {
+        *self.record_id.write().expect("record_id lock") = id;
+    }
+
+    async fn ensure_record_id(&self) -> Result<Option<String>, ProviderError> {
+        if let Some(id) = self.cached_record_id() {
             return Ok(Some(id));
         }
-        let zid = self.ensure_zone_id().await?;
-        let full = format!("{}.{}", self.record_name, self.zone_name);
+        self.lookup_record(&self.zone_name, &self.record_name, self.rtype)
+            .await?;
+        Ok(self.cached_record_id())
+    }
+
+    /// Fetch the record by name/type; caches its id when it is the
+    /// configured one.
+    async fn lookup_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<Value>, ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        let full = fqdn(name, zone);
         let v = self
-            .get(&format!(
-                "/zones/{zid}/dns_records?type={}&name={full}",
-                self.rtype_str()
-            ))
+            .get(&format!("/zones/{zid}/dns_records?type={typ}&name={full}"))
             .await?;
-        if let Some(id) = v["result"].get(0).and_then(|r| r["id"].as_str()) {
-            let _ = self.record_id.set(id.to_owned());
-            Ok(Some(self.record_id.get().unwrap()))
-        } else {
-            Ok(None)
+        let rec = v["result"].get(0).cloned();
+        if self.is_configured(zone, name, typ) {
+            self.set_record_id(
+                rec.as_ref()
+                    .and_then(|r| r["id"].as_str())
+                    .map(str::to_owned),
+            );
         }
+        Ok(rec)
     }
 
     fn rtype_str(&self) -> &'static str {
-        match self.rtype {
-            RecordType::A => "A",
-            RecordType::AAAA => "AAAA",
-        }
+        self.rtype.as_str()
     }
 
     /*──────── create / update helpers ────────*/
 
-    async fn create_record(&self, zid: &str, content: &str) -> Result<(), ProviderError> {
-        let body = json!({
-            "type":    self.rtype_str(),
-            "name":    self.record_name,
-            "content": content,
-            "ttl":     self.ttl,
-            "proxied": false
+    /// Request body for create / update.
+    ///
+    /// `HTTPS` / `SVCB` values use presentation format
+    /// (`<priority> <target> <params…>`) and are split into Cloudflare's
+    /// `data` object; `proxied` is only sent for proxiable types.
+    fn record_body(&self, content: &str) -> Result<Value, ProviderError> {
+        let mut body = json!({
+            "type": self.rtype_str(),
+            "name": self.record_name,
+            "ttl":  self.ttl,
         });
+        match self.rtype {
+            RecordType::HTTPS | RecordType::SVCB => {
+                let mut it = content.trim().splitn(3, char::is_whitespace);
+                let priority: u16 = it.next().and_then(|p| p.parse().ok()).ok_or_else(|| {
+                    ProviderError::InvalidInput(format!("bad SVCB value `{content}`"))
+                })?;
+                let target = it.next().unwrap_or(".");
+                body["data"] = json!({
+                    "priority": priority,
+                    "target":   target,
+                    "value":    it.next().unwrap_or_default().trim(),
+                });
+            }
+            RecordType::A | RecordType::AAAA | RecordType::CNAME => {
+                body["content"] = json!(content);
+                body["proxied"] = json!(false);
+            }
+            RecordType::TXT => body["content"] = json!(content),
+        }
+        Ok(body)
+    }
+
+    async fn create_record(&self, zid: &str, content: &str) -> Result<(), ProviderError> {
+        let body = self.record_body(content)?;
         let v = self
             .post(&format!("/zones/{zid}/dns_records"), body)
             .await?;
         let id = v["result"]["id"]
             .as_str()
             .ok_or_else(|| ProviderError::Api("create: missing id".into()))?;
-        let _ = self.record_id.set(id.to_owned());
+        self.set_record_id(Some(id.to_owned()));
         info!("Cloudflare created record id={id}");
         Ok(())
     }
@@ -171,13 +252,7 @@ impl CfProvider {
         rid: &str,
         content: &str,
     ) -> Result<(), ProviderError> {
-        let body = json!({
-            "type":    self.rtype_str(),
-            "name":    self.record_name,
-            "content": content,
-            "ttl":     self.ttl,
-            "proxied": false
-        });
+        let body = self.record_body(content)?;
         self.put(&format!("/zones/{zid}/dns_records/{rid}"), body)
             .await?;
         info!("Cloudflare updated record id={rid}");
@@ -185,6 +260,37 @@ impl CfProvider {
     }
 }
 
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct CfCfg {
+    token: String,
+}
+
+/// Builds `kind = "cloudflare"` entries; expects `token`.
+pub struct CfFactory;
+
+impl ProviderFactory for CfFactory {
+    fn kind(&self) -> &'static str {
+        "cloudflare"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: CfCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(CfProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            spec.ttl,
+            &c.token,
+        )?))
+    }
+}
+
 /*──────── DnsProvider impl ────────*/
 
 #[async_trait]
@@ -202,6 +308,111 @@ impl DnsProvider for CfProvider {
         self.rtype
     }
 
+    fn cache(&self) -> ProviderCache {
+        let mut c = ProviderCache::new();
+        if let Some(id) = self.zone_id.get() {
+            c.insert("zone_id".into(), id.clone());
+        }
+        if let Some(id) = self.cached_record_id() {
+            c.insert("record_id".into(), id);
+        }
+        c
+    }
+
+    fn restore_cache(&self, cache: &ProviderCache) {
+        if let Some(id) = cache.get("zone_id") {
+            let _ = self.zone_id.set(id.clone());
+        }
+        if let Some(id) = cache.get("record_id") {
+            self.set_record_id(Some(id.clone()));
+        }
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        Ok(self
+            .lookup_record(zone, name, typ)
+            .await?
+            .and_then(|r| r["content"].as_str().map(str::to_owned)))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        let suffix = format!(".{zone}");
+        let mut out = Vec::new();
+        let mut page = 1;
+        loop {
+            let v = self
+                .get(&format!(
+                    "/zones/{zid}/dns_records?per_page=1000&page={page}"
+                ))
+                .await?;
+            for r in v["result"].as_array().into_iter().flatten() {
+                let Some(typ) = r["type"].as_str().and_then(|t| t.parse().ok()) else {
+                    continue;
+                };
+                let full = r["name"].as_str().unwrap_or_default();
+                let name = match full.strip_suffix(&suffix) {
+                    Some(n) => n.to_owned(),
+                    None if full == zone => "@".to_owned(),
+                    None => full.to_owned(),
+                };
+                out.push(DnsRecord {
+                    id: r["id"].as_str().unwrap_or_default().to_owned(),
+                    name,
+                    typ,
+                    value: r["content"].as_str().unwrap_or_default().to_owned(),
+                    ttl: r["ttl"].as_u64().unwrap_or_default() as u32,
+                });
+            }
+            let pages = v["result_info"]["total_pages"].as_u64().unwrap_or(1);
+            if page >= pages {
+                break;
+            }
+            page += 1;
+        }
+        Ok(out)
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        let full = if name == "@" {
+            zone.to_owned()
+        } else {
+            format!("{name}.{zone}")
+        };
+        let v = self
+            .get(&format!("/zones/{zid}/dns_records?type={typ}&name={full}"))
+            .await?;
+        let ids: Vec<&str> = v["result"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|r| r["id"].as_str())
+            .collect();
+        if ids.is_empty() {
+            return Err(ProviderError::NotFound(format!("{typ} {full}")));
+        }
+        for rid in ids {
+            self.delete(&format!("/zones/{zid}/dns_records/{rid}"))
+                .await?;
+            info!("Cloudflare deleted record id={rid}");
+            if self.cached_record_id().as_deref() == Some(rid) {
+                self.set_record_id(None);
+            }
+        }
+        Ok(())
+    }
+
     async fn upsert_record(
         &self,
         _zone: &str,
@@ -212,7 +423,14 @@ impl DnsProvider for CfProvider {
     ) -> Result<(), ProviderError> {
         let zid = self.ensure_zone_id().await?;
         match self.ensure_record_id().await? {
-            Some(rid) => self.update_record(zid, rid, ip).await,
+            Some(rid) => match self.update_record(zid, &rid, ip).await {
+                // cached id is stale (record removed upstream) → re-create
+                Err(ProviderError::NotFound(_)) => {
+                    self.set_record_id(None);
+                    self.create_record(zid, ip).await
+                }
+                res => res,
+            },
             None => self.create_record(zid, ip).await,
         }?;
         debug!(
@@ -223,12 +441,25 @@ impl DnsProvider for CfProvider {
     }
 }
 
-/*──────── optional integration test (ignored) ────────*/
+/*──────── tests (live one ignored) ────────*/
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::env;
 
+    #[test]
+    fn svcb_body() {
+        let cf = CfProvider::new("example.com", "_svc", "HTTPS", 60, "t").unwrap();
+        let body = cf.record_body("1 . alpn=h2,h3").unwrap();
+        assert_eq!(body["data"]["priority"], 1);
+        assert_eq!(body["data"]["value"], "alpn=h2,h3");
+        // malformed user input is not worth retrying
+        assert!(matches!(
+            cf.record_body("svc.example.com. alpn=h2"),
+            Err(ProviderError::InvalidInput(_))
+        ));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     #[ignore]
     async fn live_upsert() {
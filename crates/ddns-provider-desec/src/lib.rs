@@ -0,0 +1,340 @@
+//! deSEC provider
+//!
+//! * Supports `A` / `AAAA` / `TXT` / `CNAME` / `HTTPS` / `SVCB` rrsets through
+//!   the REST API (`/api/v1/domains/<zone>/rrsets/`).
+//! * Auth via **API token** (`Authorization: Token …`).
+//! * A write is a single bulk `PATCH` of the whole rrset, which deSEC applies
+//!   atomically; no ids to cache.
+//! * TTLs are raised to the domain's `minimum_ttl` (fetched once); `0` means
+//!   "as low as allowed".
+//! * deSEC throttles hard: a 429 becomes
+//!   [`ddns_provider::ProviderError::RateLimited`] carrying `Retry-After`.
+
+use async_trait::async_trait;
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderError, ProviderFactory, ProviderSpec, ProviderTable,
+    RecordType, parse_table, record_value,
+};
+use once_cell::sync::OnceCell;
+use reqwest::{
+    Client, Response,
+    header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue, LINK, USER_AGENT},
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+const API_ROOT: &str = "https://desec.io/api/v1";
+const DEFAULT_MIN_TTL: u32 = 3600; // deSEC's default `minimum_ttl`
+
+/// deSEC names the apex `""` in bodies and `@` in URLs.
+fn subname(name: &str) -> &str {
+    if name == "@" { "" } else { name }
+}
+
+/// Human-readable text of an error body: `{"detail":…}`, or field errors
+/// (`{"ttl":["…"]}`, a list of those for bulk requests).
+fn error_text(v: &Value) -> Option<String> {
+    if let Some(d) = v["detail"].as_str() {
+        return Some(d.to_owned());
+    }
+    let mut parts = Vec::new();
+    let objs: Vec<&Value> = match v {
+        Value::Array(a) => a.iter().collect(),
+        Value::Object(_) => vec![v],
+        _ => return None,
+    };
+    for (field, msgs) in objs.iter().filter_map(|o| o.as_object()).flatten() {
+        for m in msgs.as_array().into_iter().flatten() {
+            if let Some(m) = m.as_str() {
+                parts.push(format!("{field}: {m}"));
+            }
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("; "))
+}
+
+/// `<url>; rel="next"` of a `Link` header.
+fn next_link(headers: &HeaderMap) -> Option<String> {
+    let link = headers
+        .get(LINK)?
+        .to_str()
+        .ok()?
+        .split(',')
+        .find(|l| l.contains("rel=\"next\""))?
+        .split(';')
+        .next()?
+        .trim()
+        .trim_start_matches('<')
+        .trim_end_matches('>');
+    Some(link.to_owned())
+}
+
+/*──────── provider struct ────────*/
+
+pub struct DesecProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    client: Client,
+
+    /// `minimum_ttl` of the configured zone, fetched once
+    min_ttl: OnceCell<u32>,
+}
+
+impl DesecProvider {
+    pub fn new(zone: &str, record: &str, rtype: &str, token: &str) -> anyhow::Result<Self> {
+        let mut hdr = HeaderMap::new();
+        hdr.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Token {token}"))?,
+        );
+        hdr.insert(USER_AGENT, HeaderValue::from_static("ddns-rs (+github)"));
+        hdr.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        Ok(Self {
+            zone_name: zone.to_owned(),
+            record_name: record.to_owned(),
+            rtype: rtype.parse()?,
+            client: Client::builder().default_headers(hdr).build()?,
+            min_ttl: OnceCell::new(),
+        })
+    }
+
+    /*──────── tiny HTTP wrapper ────────*/
+
+    fn rrset_url(zone: &str, name: &str, typ: RecordType) -> String {
+        let name = if name.is_empty() { "@" } else { name };
+        format!("{API_ROOT}/domains/{zone}/rrsets/{name}/{typ}/")
+    }
+
+    async fn check(&self, resp: Response) -> Result<Value, ProviderError> {
+        let status = resp.status();
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        // DELETE answers 204 without a body
+        let v: Value = resp.json().await.unwrap_or(Value::Null);
+        if status.is_success() {
+            return Ok(v);
+        }
+        let msg = error_text(&v).unwrap_or_else(|| format!("HTTP {status}"));
+        Err(ProviderError::from_status(status, msg, retry_after))
+    }
+
+    /// The rrset `name` / `typ`, `None` when it does not exist.
+    async fn rrset(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<Value>, ProviderError> {
+        let resp = self
+            .client
+            .get(Self::rrset_url(zone, name, typ))
+            .send()
+            .await?;
+        match self.check(resp).await {
+            Ok(v) => Ok(Some(v)),
+            Err(ProviderError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Lowest TTL `zone` accepts; cached for the configured zone.
+    async fn min_ttl(&self, zone: &str) -> Result<u32, ProviderError> {
+        let own = zone == self.zone_name;
+        if own && let Some(t) = self.min_ttl.get() {
+            return Ok(*t);
+        }
+        let resp = self
+            .client
+            .get(format!("{API_ROOT}/domains/{zone}/"))
+            .send()
+            .await?;
+        let v = self.check(resp).await?;
+        let t = v["minimum_ttl"]
+            .as_u64()
+            .map_or(DEFAULT_MIN_TTL, |t| t as u32);
+        if own {
+            let _ = self.min_ttl.set(t);
+        }
+        Ok(t)
+    }
+
+    /// Bulk `PATCH` of `rrsets`; applied atomically by deSEC.
+    async fn patch(&self, zone: &str, rrsets: Value) -> Result<(), ProviderError> {
+        let resp = self
+            .client
+            .patch(format!("{API_ROOT}/domains/{zone}/rrsets/"))
+            .json(&rrsets)
+            .send()
+            .await?;
+        self.check(resp).await.map(|_| ())
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct DesecCfg {
+    token: String,
+}
+
+/// Builds `kind = "desec"` entries; expects `token`.
+pub struct DesecFactory;
+
+impl ProviderFactory for DesecFactory {
+    fn kind(&self) -> &'static str {
+        "desec"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: DesecCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(DesecProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            &c.token,
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for DesecProvider {
+    fn name(&self) -> &'static str {
+        "deSEC"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        Ok(self
+            .rrset(zone, subname(name), typ)
+            .await?
+            .and_then(|s| s["records"][0].as_str().map(str::to_owned)))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        let mut out = Vec::new();
+        // zones above 500 rrsets are paginated through `Link: <…>; rel="next"`
+        let mut url = Some(format!("{API_ROOT}/domains/{zone}/rrsets/?cursor="));
+        while let Some(u) = url {
+            let resp = self.client.get(u).send().await?;
+            url = next_link(resp.headers());
+            let v = self.check(resp).await?;
+            for s in v.as_array().into_iter().flatten() {
+                let (Some(sub), Some(Ok(typ))) = (
+                    s["subname"].as_str(),
+                    s["type"].as_str().map(str::parse::<RecordType>),
+                ) else {
+                    continue;
+                };
+                let name = if sub.is_empty() { "@" } else { sub };
+                for r in s["records"].as_array().into_iter().flatten() {
+                    out.push(DnsRecord {
+                        id: format!("{name}/{typ}"),
+                        name: name.to_owned(),
+                        typ,
+                        value: r.as_str().unwrap_or_default().to_owned(),
+                        ttl: s["ttl"].as_u64().unwrap_or_default() as u32,
+                    });
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        // DELETE of a missing rrset succeeds silently; report it like the others
+        if self.rrset(zone, subname(name), typ).await?.is_none() {
+            return Err(ProviderError::NotFound(format!("{typ} {name}.{zone}")));
+        }
+        let resp = self
+            .client
+            .delete(Self::rrset_url(zone, subname(name), typ))
+            .send()
+            .await?;
+        self.check(resp).await?;
+        info!("deSEC deleted {typ} {name}.{zone}");
+        Ok(())
+    }
+
+    async fn upsert_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+        ip: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        let ttl = ttl.max(self.min_ttl(zone).await?);
+        self.patch(
+            zone,
+            json!([{
+                "subname": subname(name),
+                "type":    typ.as_str(),
+                "ttl":     ttl,
+                "records": [record_value(typ, ip)],
+            }]),
+        )
+        .await?;
+        debug!("deSEC upsert {name}.{zone} -> {ip} (ttl {ttl})");
+        Ok(())
+    }
+}
+
+/*──────── tests ────────*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn error_bodies() {
+        assert_eq!(
+            error_text(&json!({ "detail": "Invalid token." })).as_deref(),
+            Some("Invalid token.")
+        );
+        assert_eq!(
+            error_text(
+                &json!([{ "ttl": ["Ensure this value is greater than or equal to 3600."] }])
+            )
+            .as_deref(),
+            Some("ttl: Ensure this value is greater than or equal to 3600.")
+        );
+        assert_eq!(error_text(&Value::Null), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn live_upsert() {
+        let token = env::var("DESEC_TOKEN").expect("DESEC_TOKEN not set");
+        let p = DesecProvider::new("example.com", "test-ddns", "A", &token).unwrap();
+        p.upsert_record("example.com", "test-ddns", RecordType::A, "1.1.1.1", 0)
+            .await
+            .unwrap();
+    }
+}
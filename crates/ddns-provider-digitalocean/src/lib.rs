@@ -0,0 +1,383 @@
+//! DigitalOcean DNS provider
+//!
+//! * Supports `A` / `AAAA` / `TXT` / `CNAME` record *upsert* (create or update).
+//! * Auth via **personal access token** with read / write scope.
+//! * The domain name is the zone id; the `record_id` is cached locally.
+//! * Errors are mapped to [`ddns_provider::ProviderError`] by the API's error
+//!   `id` (auth / not-found / rate-limit / invalid input).
+//! * A cached `record_id` that vanished upstream is dropped and the record re-created.
+
+use async_trait::async_trait;
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderCache, ProviderError, ProviderFactory, ProviderSpec,
+    ProviderTable, RecordType, name::fqdn, parse_table,
+};
+use reqwest::{
+    Client, Response,
+    header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue, USER_AGENT},
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::{Arc, RwLock};
+use tracing::{debug, info};
+
+const API_ROOT: &str = "https://api.digitalocean.com/v2";
+const DEFAULT_TTL: u32 = 1800; // DigitalOcean's own default
+const MIN_TTL: u32 = 30;
+
+/*──────── provider struct ────────*/
+
+pub struct DigitalOceanProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    client: Client,
+
+    record_id: RwLock<Option<u64>>,
+}
+
+impl DigitalOceanProvider {
+    pub fn new(zone: &str, record: &str, rtype: &str, token: &str) -> anyhow::Result<Self> {
+        let rtype: RecordType = rtype.parse()?;
+        if matches!(rtype, RecordType::HTTPS | RecordType::SVCB) {
+            anyhow::bail!("digitalocean: {rtype} records are not supported");
+        }
+        let mut hdr = HeaderMap::new();
+        hdr.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {token}"))?,
+        );
+        hdr.insert(USER_AGENT, HeaderValue::from_static("ddns-rs (+github)"));
+        hdr.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        Ok(Self {
+            zone_name: zone.to_owned(),
+            record_name: record.to_owned(),
+            rtype,
+            client: Client::builder().default_headers(hdr).build()?,
+            record_id: RwLock::new(None),
+        })
+    }
+
+    /*──────── tiny HTTP wrapper ────────*/
+
+    async fn get(&self, path: &str) -> Result<Value, ProviderError> {
+        self.check(self.client.get(format!("{API_ROOT}{path}")).send().await?)
+            .await
+    }
+    async fn post(&self, path: &str, body: Value) -> Result<Value, ProviderError> {
+        self.check(
+            self.client
+                .post(format!("{API_ROOT}{path}"))
+                .json(&body)
+                .send()
+                .await?,
+        )
+        .await
+    }
+    async fn put(&self, path: &str, body: Value) -> Result<Value, ProviderError> {
+        self.check(
+            self.client
+                .put(format!("{API_ROOT}{path}"))
+                .json(&body)
+                .send()
+                .await?,
+        )
+        .await
+    }
+
+    async fn delete(&self, path: &str) -> Result<Value, ProviderError> {
+        self.check(
+            self.client
+                .delete(format!("{API_ROOT}{path}"))
+                .send()
+                .await?,
+        )
+        .await
+    }
+
+    async fn check(&self, resp: Response) -> Result<Value, ProviderError> {
+        let status = resp.status();
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        // DELETE answers 204 without a body
+        let v: Value = resp.json().await.unwrap_or(Value::Null);
+        if status.is_success() {
+            return Ok(v);
+        }
+        let msg = v["message"]
+            .as_str()
+            .map(str::to_owned)
+            .unwrap_or_else(|| format!("HTTP {status}"));
+        Err(match v["id"].as_str() {
+            Some("unauthorized" | "forbidden") => ProviderError::Auth(msg),
+            Some("not_found") => ProviderError::NotFound(msg),
+            Some("too_many_requests") => ProviderError::RateLimited { retry_after },
+            Some("bad_request" | "unprocessable_entity") => ProviderError::InvalidInput(msg),
+            Some("server_error" | "service_unavailable") => ProviderError::Transient(msg),
+            _ => ProviderError::from_status(status, msg, retry_after),
+        })
+    }
+
+    /*──────── record helpers ────────*/
+
+    fn cached_record_id(&self) -> Option<u64> {
+        *self.record_id.read().expect("record_id lock")
+    }
+
+    fn set_record_id(&self, id: Option<u64>) {
+        *self.record_id.write().expect("record_id lock") = id;
+    }
+
+    async fn ensure_record_id(&self) -> Result<Option<u64>, ProviderError> {
+        if let Some(id) = self.cached_record_id() {
+            return Ok(Some(id));
+        }
+        self.lookup_record(&self.zone_name, &self.record_name, self.rtype)
+            .await?;
+        Ok(self.cached_record_id())
+    }
+
+    /// Fetch the record by name/type; caches its id when it is the
+    /// configured one.
+    async fn lookup_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<Value>, ProviderError> {
+        let v = self
+            .get(&format!(
+                "/domains/{zone}/records?type={typ}&name={}",
+                fqdn(name, zone)
+            ))
+            .await?;
+        let rec = v["domain_records"].get(0).cloned();
+        if self.is_configured(zone, name, typ) {
+            self.set_record_id(rec.as_ref().and_then(|r| r["id"].as_u64()));
+        }
+        Ok(rec)
+    }
+
+    /*──────── create / update helpers ────────*/
+
+    fn record_body(&self, content: &str, ttl: u32) -> Value {
+        let ttl = if ttl == 0 {
+            DEFAULT_TTL
+        } else {
+            ttl.max(MIN_TTL)
+        };
+        // CNAME targets are stored absolute
+        let data = match self.rtype {
+            RecordType::CNAME if !content.ends_with('.') => format!("{content}."),
+            _ => content.to_owned(),
+        };
+        json!({
+            "type": self.rtype.as_str(),
+            "name": self.record_name,
+            "data": data,
+            "ttl":  ttl,
+        })
+    }
+
+    async fn create_record(&self, content: &str, ttl: u32) -> Result<(), ProviderError> {
+        let v = self
+            .post(
+                &format!("/domains/{}/records", self.zone_name),
+                self.record_body(content, ttl),
+            )
+            .await?;
+        let id = v["domain_record"]["id"]
+            .as_u64()
+            .ok_or_else(|| ProviderError::Api("create: missing id".into()))?;
+        self.set_record_id(Some(id));
+        info!("DigitalOcean created record id={id}");
+        Ok(())
+    }
+
+    async fn update_record(&self, rid: u64, content: &str, ttl: u32) -> Result<(), ProviderError> {
+        self.put(
+            &format!("/domains/{}/records/{rid}", self.zone_name),
+            self.record_body(content, ttl),
+        )
+        .await?;
+        info!("DigitalOcean updated record id={rid}");
+        Ok(())
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct DigitalOceanCfg {
+    token: String,
+}
+
+/// Builds `kind = "digitalocean"` entries; expects `token`.
+pub struct DigitalOceanFactory;
+
+impl ProviderFactory for DigitalOceanFactory {
+    fn kind(&self) -> &'static str {
+        "digitalocean"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: DigitalOceanCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(DigitalOceanProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            &c.token,
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for DigitalOceanProvider {
+    fn name(&self) -> &'static str {
+        "DigitalOcean"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    fn cache(&self) -> ProviderCache {
+        let mut c = ProviderCache::new();
+        if let Some(id) = self.cached_record_id() {
+            c.insert("record_id".into(), id.to_string());
+        }
+        c
+    }
+
+    fn restore_cache(&self, cache: &ProviderCache) {
+        if let Some(id) = cache.get("record_id").and_then(|s| s.parse().ok()) {
+            self.set_record_id(Some(id));
+        }
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        Ok(self
+            .lookup_record(zone, name, typ)
+            .await?
+            .and_then(|r| r["data"].as_str().map(str::to_owned)))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        let mut out = Vec::new();
+        let mut page = 1;
+        loop {
+            let v = self
+                .get(&format!("/domains/{zone}/records?per_page=200&page={page}"))
+                .await?;
+            for r in v["domain_records"].as_array().into_iter().flatten() {
+                let Some(typ) = r["type"].as_str().and_then(|t| t.parse().ok()) else {
+                    continue;
+                };
+                out.push(DnsRecord {
+                    id: r["id"].as_u64().unwrap_or_default().to_string(),
+                    name: r["name"].as_str().unwrap_or("@").to_owned(),
+                    typ,
+                    value: r["data"].as_str().unwrap_or_default().to_owned(),
+                    ttl: r["ttl"].as_u64().unwrap_or_default() as u32,
+                });
+            }
+            if v["links"]["pages"]["next"].is_null() {
+                break;
+            }
+            page += 1;
+        }
+        Ok(out)
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        let full = if name == "@" {
+            zone.to_owned()
+        } else {
+            format!("{name}.{zone}")
+        };
+        let v = self
+            .get(&format!("/domains/{zone}/records?type={typ}&name={full}"))
+            .await?;
+        let ids: Vec<u64> = v["domain_records"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|r| r["id"].as_u64())
+            .collect();
+        if ids.is_empty() {
+            return Err(ProviderError::NotFound(format!("{typ} {full}")));
+        }
+        for rid in ids {
+            self.delete(&format!("/domains/{zone}/records/{rid}"))
+                .await?;
+            info!("DigitalOcean deleted record id={rid}");
+            if self.cached_record_id() == Some(rid) {
+                self.set_record_id(None);
+            }
+        }
+        Ok(())
+    }
+
+    async fn upsert_record(
+        &self,
+        _zone: &str,
+        _name: &str,
+        _typ: RecordType,
+        ip: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        match self.ensure_record_id().await? {
+            Some(rid) => match self.update_record(rid, ip, ttl).await {
+                Err(ProviderError::NotFound(_)) => {
+                    self.set_record_id(None);
+                    self.create_record(ip, ttl).await
+                }
+                res => res,
+            },
+            None => self.create_record(ip, ttl).await,
+        }?;
+        debug!(
+            "DigitalOcean upsert {}.{} -> {}",
+            self.record_name, self.zone_name, ip
+        );
+        Ok(())
+    }
+}
+
+/*──────── optional integration test (ignored) ────────*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn live_upsert() {
+        let token = env::var("DO_TOKEN").expect("DO_TOKEN not set");
+        let p = DigitalOceanProvider::new("example.com", "test-ddns", "A", &token).unwrap();
+        p.upsert_record("example.com", "test-ddns", RecordType::A, "1.1.1.1", 60)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,234 @@
+//! DuckDNS provider
+//!
+//! * `GET https://www.duckdns.org/update?domains=…&token=…&ip=…&ipv6=…`
+//! * One token updates several subdomains at once: `record` plus the
+//!   optional `domains` list.
+//! * `A` / `AAAA` only. A value holding both families (e.g. the template
+//!   `value = "{ipv4},{ipv6}"`) sets both records in a single request.
+//! * DuckDNS answers `OK` or `KO`; `KO` (bad token or foreign subdomain) is
+//!   reported as [`ddns_provider::ProviderError::Auth`] and not retried.
+
+use async_trait::async_trait;
+use ddns_provider::{
+    DnsProvider, ProviderError, ProviderFactory, ProviderSpec, ProviderTable, RecordType,
+    parse_table,
+};
+use reqwest::Client;
+use serde::Deserialize;
+use std::{net::IpAddr, sync::Arc};
+use tracing::debug;
+
+const DEFAULT_SERVER: &str = "https://www.duckdns.org";
+const SUFFIX: &str = ".duckdns.org";
+
+/// Split an update value into its IPv4 and IPv6 parts (`"1.2.3.4,2001:db8::1"`).
+fn split_ips(value: &str) -> Result<(Option<String>, Option<String>), ProviderError> {
+    let (mut v4, mut v6) = (None, None);
+    for part in value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|p| !p.is_empty())
+    {
+        let slot = match part.parse::<IpAddr>() {
+            Ok(IpAddr::V4(_)) => &mut v4,
+            Ok(IpAddr::V6(_)) => &mut v6,
+            Err(_) => {
+                return Err(ProviderError::InvalidInput(format!(
+                    "`{part}` is not an IP address"
+                )));
+            }
+        };
+        if slot.replace(part.to_owned()).is_some() {
+            return Err(ProviderError::InvalidInput(format!(
+                "more than one address of the same family in `{value}`"
+            )));
+        }
+    }
+    if v4.is_none() && v6.is_none() {
+        return Err(ProviderError::InvalidInput("no address to update".into()));
+    }
+    Ok((v4, v6))
+}
+
+/// Map the first line of an update reply to a result.
+fn parse_reply(body: &str) -> Result<(), ProviderError> {
+    match body.lines().next().unwrap_or_default().trim() {
+        "OK" => Ok(()),
+        "KO" => Err(ProviderError::Auth(
+            "KO: token rejected or subdomain not owned by this account".into(),
+        )),
+        "" => Err(ProviderError::Api("empty reply".into())),
+        other => Err(ProviderError::Api(other.into())),
+    }
+}
+
+/// `myhome.duckdns.org` / `myhome` → `myhome`
+fn subdomain(name: &str) -> &str {
+    name.trim_end_matches('.').trim_end_matches(SUFFIX)
+}
+
+/*──────── provider struct ────────*/
+
+pub struct DuckDnsProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    url: String,
+    token: String,
+    /// comma-separated subdomains sent as `domains=`
+    domains: String,
+    client: Client,
+}
+
+impl DuckDnsProvider {
+    /// `domains` are extra subdomains updated together with `record`.
+    pub fn new(
+        zone: &str,
+        record: &str,
+        record_type: &str,
+        server: &str,
+        token: &str,
+        domains: &[String],
+    ) -> anyhow::Result<Self> {
+        let rtype: RecordType = record_type.parse()?;
+        if !rtype.is_address() {
+            anyhow::bail!("duckdns only updates A / AAAA records, not {rtype}");
+        }
+        let first = if record == "@" { zone } else { record };
+        let mut names: Vec<&str> = vec![subdomain(first)];
+        for d in domains.iter().map(|d| subdomain(d)) {
+            if !names.contains(&d) {
+                names.push(d);
+            }
+        }
+        if names.iter().any(|n| n.is_empty() || n.contains('.')) {
+            anyhow::bail!("duckdns: invalid subdomain in `{}`", names.join(","));
+        }
+        Ok(Self {
+            zone_name: zone.to_owned(),
+            record_name: record.to_owned(),
+            rtype,
+            url: format!("{}/update", server.trim_end_matches('/')),
+            token: token.to_owned(),
+            domains: names.join(","),
+            client: Client::new(),
+        })
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct DuckDnsCfg {
+    token: String,
+    #[serde(default)]
+    domains: Vec<String>,
+    #[serde(default = "default_server")]
+    server: String,
+}
+
+fn default_server() -> String {
+    DEFAULT_SERVER.into()
+}
+
+/// Builds `kind = "duckdns"` entries; expects `token` and optionally
+/// `domains` and `server`.
+pub struct DuckDnsFactory;
+
+impl ProviderFactory for DuckDnsFactory {
+    fn kind(&self) -> &'static str {
+        "duckdns"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: DuckDnsCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(DuckDnsProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            &c.server,
+            &c.token,
+            &c.domains,
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for DuckDnsProvider {
+    fn name(&self) -> &'static str {
+        "DuckDNS"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    async fn upsert_record(
+        &self,
+        _zone: &str,
+        _name: &str,
+        _typ: RecordType,
+        ip: &str,
+        _ttl: u32,
+    ) -> Result<(), ProviderError> {
+        let (v4, v6) = split_ips(ip)?;
+        let mut query = vec![
+            ("domains", self.domains.as_str()),
+            ("token", self.token.as_str()),
+        ];
+        if let Some(v) = &v4 {
+            query.push(("ip", v));
+        }
+        if let Some(v) = &v6 {
+            query.push(("ipv6", v));
+        }
+
+        let resp = self.client.get(&self.url).query(&query).send().await?;
+        let status = resp.status();
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        let body = resp.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(ProviderError::from_status(
+                status,
+                format!("HTTP {status}: {}", body.trim()),
+                retry_after,
+            ));
+        }
+        parse_reply(&body)?;
+        debug!("DuckDNS update {} -> {ip}", self.domains);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_and_replies() {
+        assert_eq!(
+            split_ips("1.2.3.4,2001:db8::1").unwrap(),
+            (Some("1.2.3.4".into()), Some("2001:db8::1".into()))
+        );
+        assert_eq!(
+            split_ips("2001:db8::1").unwrap(),
+            (None, Some("2001:db8::1".into()))
+        );
+        assert!(split_ips("1.2.3.4 5.6.7.8").is_err());
+        assert!(split_ips("home").is_err());
+
+        assert!(parse_reply("OK").is_ok());
+        assert!(!parse_reply("KO").unwrap_err().is_retryable());
+        assert_eq!(subdomain("myhome.duckdns.org"), "myhome");
+    }
+}
@@ -0,0 +1,246 @@
+//! DynDNS2 protocol provider
+//!
+//! * `GET <server>/nic/update?hostname=…&myip=…` with HTTP basic auth – spoken by
+//!   No-IP, Dynu, DynDNS, FreeDNS, many routers and self-hosted servers.
+//! * Only `A` / `AAAA` records; the service decides TTL and zone layout.
+//! * Return codes (`good`, `nochg`, `badauth`, `nohost`, `abuse`, …) are mapped to
+//!   [`ddns_provider::ProviderError`].
+//! * After `badauth` or `abuse` the protocol forbids further automatic updates:
+//!   the provider refuses to send requests until the process is restarted.
+
+use async_trait::async_trait;
+use ddns_provider::{
+    DnsProvider, ProviderError, ProviderFactory, ProviderSpec, ProviderTable, RecordType,
+    parse_table,
+};
+use reqwest::{Client, StatusCode, header::USER_AGENT};
+use serde::Deserialize;
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tracing::{debug, error};
+
+const DEFAULT_SERVER: &str = "https://members.dyndns.org";
+const UPDATE_PATH: &str = "/nic/update";
+/// `911`: the protocol asks clients to back off for at least 30 minutes.
+const BACKOFF_911: Duration = Duration::from_secs(30 * 60);
+
+/// Map the first line of an update reply to a result.
+fn parse_reply(body: &str) -> Result<(), ProviderError> {
+    let line = body.lines().next().unwrap_or_default().trim();
+    let code = line.split_whitespace().next().unwrap_or_default();
+    let text = if line.is_empty() { "empty reply" } else { line };
+    match code {
+        "good" | "nochg" => Ok(()),
+        "badauth" | "abuse" => Err(ProviderError::Auth(text.into())),
+        "nohost" => Err(ProviderError::NotFound(text.into())),
+        "notfqdn" | "numhost" | "badagent" | "!donator" | "!yours" => {
+            Err(ProviderError::InvalidInput(text.into()))
+        }
+        "dnserr" => Err(ProviderError::Transient(text.into())),
+        "911" => Err(ProviderError::RateLimited {
+            retry_after: Some(BACKOFF_911),
+        }),
+        _ => Err(ProviderError::Api(text.into())),
+    }
+}
+
+/*──────── provider struct ────────*/
+
+pub struct DynDns2Provider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    url: String,
+    hostname: String,
+    username: String,
+    password: String,
+    client: Client,
+
+    /// set after `badauth` / `abuse`; no request is sent afterwards
+    halted: RwLock<Option<String>>,
+}
+
+impl DynDns2Provider {
+    /// `server` is a base URL (`/nic/update` is appended) or a full update
+    /// URL; `hostname` defaults to `record.zone`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        zone: &str,
+        record: &str,
+        record_type: &str,
+        server: &str,
+        hostname: Option<&str>,
+        username: &str,
+        password: &str,
+    ) -> anyhow::Result<Self> {
+        let rtype: RecordType = record_type.parse()?;
+        if !rtype.is_address() {
+            anyhow::bail!("dyndns2 only updates A / AAAA records, not {rtype}");
+        }
+        let server = server.trim_end_matches('/');
+        let url = if server.ends_with(UPDATE_PATH) || server.contains('?') {
+            server.to_owned()
+        } else {
+            format!("{server}{UPDATE_PATH}")
+        };
+        let hostname = hostname.map(str::to_owned).unwrap_or_else(|| {
+            if record == "@" {
+                zone.to_owned()
+            } else {
+                format!("{record}.{zone}")
+            }
+        });
+        Ok(Self {
+            zone_name: zone.to_owned(),
+            record_name: record.to_owned(),
+            rtype,
+            url,
+            hostname,
+            username: username.to_owned(),
+            password: password.to_owned(),
+            client: Client::new(),
+            halted: RwLock::new(None),
+        })
+    }
+
+    fn halt(&self, reason: &str) {
+        *self.halted.write().expect("halted lock") = Some(reason.to_owned());
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct DynDns2Cfg {
+    #[serde(default = "default_server")]
+    server: String,
+    #[serde(default)]
+    hostname: Option<String>,
+    username: String,
+    password: String,
+}
+
+fn default_server() -> String {
+    DEFAULT_SERVER.into()
+}
+
+/// Builds `kind = "dyndns2"` entries; expects `username`, `password` and
+/// optionally `server` and `hostname`.
+pub struct DynDns2Factory;
+
+impl ProviderFactory for DynDns2Factory {
+    fn kind(&self) -> &'static str {
+        "dyndns2"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: DynDns2Cfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(DynDns2Provider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            &c.server,
+            c.hostname.as_deref(),
+            &c.username,
+            &c.password,
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for DynDns2Provider {
+    fn name(&self) -> &'static str {
+        "DynDNS2"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    async fn upsert_record(
+        &self,
+        _zone: &str,
+        _name: &str,
+        _typ: RecordType,
+        ip: &str,
+        _ttl: u32,
+    ) -> Result<(), ProviderError> {
+        if let Some(reason) = self.halted.read().expect("halted lock").as_deref() {
+            return Err(ProviderError::Auth(format!(
+                "{reason} (updates halted until restart)"
+            )));
+        }
+
+        let resp = self
+            .client
+            .get(&self.url)
+            .basic_auth(&self.username, Some(&self.password))
+            .header(USER_AGENT, concat!("ddns-rs/", env!("CARGO_PKG_VERSION")))
+            .query(&[("hostname", self.hostname.as_str()), ("myip", ip)])
+            .send()
+            .await?;
+        let status = resp.status();
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        let body = resp.text().await.unwrap_or_default();
+
+        // some servers answer 401 without a body instead of `badauth`
+        let res = if status == StatusCode::UNAUTHORIZED && !body.starts_with("badauth") {
+            Err(ProviderError::Auth(format!("badauth (HTTP {status})")))
+        } else if !status.is_success() && body.trim().is_empty() {
+            Err(ProviderError::from_status(
+                status,
+                format!("HTTP {status}"),
+                retry_after,
+            ))
+        } else {
+            parse_reply(&body)
+        };
+        if let Err(ProviderError::Auth(reason)) = &res {
+            error!("DynDNS2 {}: {reason}; halting updates", self.hostname);
+            self.halt(reason);
+        }
+        res?;
+        debug!("DynDNS2 update {} -> {ip}", self.hostname);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn return_codes() {
+        assert!(parse_reply("good 1.2.3.4").is_ok());
+        assert!(parse_reply("nochg 1.2.3.4\n").is_ok());
+        assert!(matches!(
+            parse_reply("badauth"),
+            Err(ProviderError::Auth(_))
+        ));
+        assert!(matches!(parse_reply("abuse"), Err(ProviderError::Auth(_))));
+        assert!(matches!(
+            parse_reply("nohost"),
+            Err(ProviderError::NotFound(_))
+        ));
+        assert!(matches!(
+            parse_reply("911"),
+            Err(ProviderError::RateLimited {
+                retry_after: Some(_)
+            })
+        ));
+        assert!(!parse_reply("badauth").unwrap_err().is_retryable());
+    }
+}
@@ -0,0 +1,384 @@
+//! External-process DNS provider
+//!
+//! * Runs a configured program per operation (`upsert` / `get` / `list` / `delete`).
+//! * Request is one JSON object on **stdin**, reply one JSON object on **stdout**.
+//! * Non-zero exit codes and `"ok": false` replies are mapped to [`ddns_provider::ProviderError`]
+//!   through the reply's `kind`.
+//! * The process is killed once `timeout` elapses.
+
+use async_trait::async_trait;
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderError, ProviderFactory, ProviderSpec, ProviderTable,
+    RecordType, parse_table,
+};
+use serde::{Deserialize, Serialize};
+use std::{process::Stdio, sync::Arc, time::Duration};
+use tokio::{io::AsyncWriteExt, process::Command, time::timeout};
+use tracing::{debug, info};
+
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+
+/*──────── wire format ────────*/
+
+#[derive(Serialize)]
+struct Request<'a> {
+    operation: &'a str,
+    zone: &'a str,
+    record: &'a str,
+    #[serde(rename = "type")]
+    typ: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<&'a str>,
+    ttl: u32,
+}
+
+#[derive(Deserialize)]
+struct Reply {
+    #[serde(default = "default_true")]
+    ok: bool,
+    #[serde(default)]
+    value: Option<String>,
+    #[serde(default)]
+    records: Vec<ReplyRecord>,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    kind: Option<String>,
+    #[serde(default)]
+    retry_after: Option<u64>,
+}
+
+impl Default for Reply {
+    fn default() -> Self {
+        Self {
+            ok: true,
+            value: None,
+            records: Vec::new(),
+            error: None,
+            kind: None,
+            retry_after: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ReplyRecord {
+    #[serde(default)]
+    id: String,
+    name: String,
+    #[serde(rename = "type")]
+    typ: String,
+    value: String,
+    #[serde(default)]
+    ttl: u32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/*──────── provider struct ────────*/
+
+pub struct ExecProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    ttl: u32,
+    command: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl ExecProvider {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        zone: &str,
+        record: &str,
+        record_type: &str,
+        ttl: u32,
+        command: &str,
+        args: Vec<String>,
+        timeout: Duration,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            zone_name: zone.to_owned(),
+            record_name: record.to_owned(),
+            rtype: record_type.parse()?,
+            ttl,
+            command: command.to_owned(),
+            args,
+            timeout,
+        })
+    }
+
+    /*──────── process helper ────────*/
+
+    async fn run(&self, op: &'static str, req: &Request<'_>) -> Result<Reply, ProviderError> {
+        let body = serde_json::to_vec(req).expect("request is always serializable");
+        let mut child = Command::new(&self.command)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| ProviderError::InvalidInput(format!("spawn `{}`: {e}", self.command)))?;
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let fut = async move {
+            // a plugin may exit without reading its input; that is not our failure
+            let _ = stdin.write_all(&body).await;
+            drop(stdin);
+            child.wait_with_output().await
+        };
+        let out = match timeout(self.timeout, fut).await {
+            Ok(res) => res.map_err(|e| ProviderError::Transient(e.to_string()))?,
+            Err(_) => {
+                return Err(ProviderError::Transient(format!(
+                    "`{}` timed out after {}ms",
+                    self.command,
+                    self.timeout.as_millis()
+                )));
+            }
+        };
+
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        let stdout = stdout.trim();
+        if out.status.success() {
+            let reply = if stdout.is_empty() {
+                Reply::default()
+            } else {
+                serde_json::from_str(stdout)
+                    .map_err(|e| ProviderError::Api(format!("{op}: bad reply: {e}")))?
+            };
+            if reply.ok {
+                return Ok(reply);
+            }
+            return Err(classify(op, reply, String::new()));
+        }
+
+        // failed: prefer a structured reply, fall back to stderr
+        let reply = serde_json::from_str(stdout).unwrap_or_default();
+        let stderr = String::from_utf8_lossy(&out.stderr).trim().to_owned();
+        let fallback = if stderr.is_empty() {
+            format!("`{}` exited with {}", self.command, out.status)
+        } else {
+            stderr
+        };
+        Err(classify(op, reply, fallback))
+    }
+
+    fn request<'a>(&'a self, operation: &'a str, value: Option<&'a str>) -> Request<'a> {
+        Request {
+            operation,
+            zone: &self.zone_name,
+            record: &self.record_name,
+            typ: self.rtype.as_str(),
+            value,
+            ttl: self.ttl,
+        }
+    }
+}
+
+/// Turn a failure reply into a [`ProviderError`] using its `kind`.
+fn classify(op: &'static str, reply: Reply, fallback: String) -> ProviderError {
+    let msg = reply.error.filter(|e| !e.is_empty()).unwrap_or_else(|| {
+        if fallback.is_empty() {
+            format!("{op} failed")
+        } else {
+            fallback
+        }
+    });
+    match reply.kind.as_deref() {
+        Some("auth") => ProviderError::Auth(msg),
+        Some("not_found") => ProviderError::NotFound(msg),
+        Some("rate_limited") => ProviderError::RateLimited {
+            retry_after: reply.retry_after.map(Duration::from_secs),
+        },
+        Some("invalid_input") => ProviderError::InvalidInput(msg),
+        Some("transient") => ProviderError::Transient(msg),
+        Some("unsupported") => ProviderError::Unsupported(op),
+        _ => ProviderError::Api(msg),
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct ExecCfg {
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+    /// timeout in milliseconds
+    #[serde(default)]
+    timeout: Option<u64>,
+}
+
+/// Builds `kind = "exec"` entries; expects `command`, optionally `args` and
+/// `timeout` (ms).
+pub struct ExecFactory;
+
+impl ProviderFactory for ExecFactory {
+    fn kind(&self) -> &'static str {
+        "exec"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: ExecCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(ExecProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            spec.ttl,
+            &c.command,
+            c.args,
+            Duration::from_millis(c.timeout.unwrap_or(DEFAULT_TIMEOUT_MS)),
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for ExecProvider {
+    fn name(&self) -> &'static str {
+        "Exec"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        let req = Request {
+            zone,
+            record: name,
+            typ: typ.as_str(),
+            ..self.request("get", None)
+        };
+        let reply = self.run("get_record", &req).await?;
+        Ok(reply.value)
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        let req = Request {
+            zone,
+            ..self.request("list", None)
+        };
+        let reply = self.run("list_records", &req).await?;
+        Ok(reply
+            .records
+            .into_iter()
+            .filter_map(|r| {
+                Some(DnsRecord {
+                    id: r.id,
+                    name: r.name,
+                    typ: r.typ.parse().ok()?,
+                    value: r.value,
+                    ttl: r.ttl,
+                })
+            })
+            .collect())
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        let req = Request {
+            zone,
+            record: name,
+            typ: typ.as_str(),
+            ..self.request("delete", None)
+        };
+        self.run("delete_record", &req).await?;
+        info!("Exec deleted {typ} {name}.{zone}");
+        Ok(())
+    }
+
+    async fn upsert_record(
+        &self,
+        _zone: &str,
+        _name: &str,
+        _typ: RecordType,
+        ip: &str,
+        _ttl: u32,
+    ) -> Result<(), ProviderError> {
+        self.run("upsert_record", &self.request("upsert", Some(ip)))
+            .await?;
+        debug!(
+            "Exec upsert {}.{} -> {}",
+            self.record_name, self.zone_name, ip
+        );
+        Ok(())
+    }
+}
+
+/*──────── local tests (POSIX `sh`) ────────*/
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str) -> ExecProvider {
+        ExecProvider::new(
+            "example.com",
+            "home",
+            "A",
+            60,
+            "sh",
+            vec!["-c".into(), script.into()],
+            Duration::from_millis(500),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn request_and_reply() {
+        // echo the received value back as the current one
+        let p = sh(r#"read req; v=${req#*\"value\":\"}; echo "{\"value\":\"${v%%\"*}\"}""#);
+        let req = p.request("upsert", Some("1.2.3.4"));
+        let reply = p.run("get_record", &req).await.unwrap();
+        assert_eq!(reply.value.as_deref(), Some("1.2.3.4"));
+    }
+
+    #[tokio::test]
+    async fn get_record_asks_for_the_given_record() {
+        // answer with the requested record name and type
+        let p = sh(
+            r#"read req; r=${req#*\"record\":\"}; t=${req#*\"type\":\"}; echo "{\"value\":\"${r%%\"*} ${t%%\"*}\"}""#,
+        );
+        let live = p.get_record("example.org", "www", RecordType::AAAA).await;
+        assert_eq!(live.unwrap().as_deref(), Some("www AAAA"));
+    }
+
+    #[tokio::test]
+    async fn failures_are_classified() {
+        let p = sh(r#"echo '{"ok":false,"kind":"auth","error":"bad key"}'"#);
+        let err = p.upsert_record("", "", RecordType::A, "1.2.3.4", 60).await;
+        assert!(matches!(err, Err(ProviderError::Auth(m)) if m == "bad key"));
+
+        let p = sh("echo boom >&2; exit 3");
+        let err = p.upsert_record("", "", RecordType::A, "1.2.3.4", 60).await;
+        assert!(matches!(err, Err(ProviderError::Api(m)) if m == "boom"));
+
+        let p = sh("sleep 5");
+        let err = p.upsert_record("", "", RecordType::A, "1.2.3.4", 60).await;
+        assert!(matches!(err, Err(ProviderError::Transient(_))));
+    }
+}
@@ -0,0 +1,244 @@
+//! Gandi LiveDNS provider
+//!
+//! * Supports `A` / `AAAA` / `TXT` / `CNAME` / `HTTPS` / `SVCB` rrsets through
+//!   LiveDNS v5 (`/livedns/domains/<zone>/records/<name>/<type>`).
+//! * Auth via **personal access token** (`Authorization: Bearer`) with the
+//!   *Manage domain name technical configurations* permission.
+//! * rrsets are addressed by name and type, so a write is a single `PUT` that
+//!   creates or replaces the set; no ids to cache.
+//! * Errors are mapped to [`ddns_provider::ProviderError`] by HTTP status.
+
+use async_trait::async_trait;
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderError, ProviderFactory, ProviderSpec, ProviderTable,
+    RecordType, parse_table, record_value,
+};
+use reqwest::{
+    Client, Response,
+    header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue, USER_AGENT},
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+const API_ROOT: &str = "https://api.gandi.net/v5/livedns";
+const MIN_TTL: u32 = 300; // LiveDNS rejects anything lower; also used for 0
+
+/*──────── provider struct ────────*/
+
+pub struct GandiProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    client: Client,
+}
+
+impl GandiProvider {
+    pub fn new(zone: &str, record: &str, rtype: &str, token: &str) -> anyhow::Result<Self> {
+        let mut hdr = HeaderMap::new();
+        hdr.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {token}"))?,
+        );
+        hdr.insert(USER_AGENT, HeaderValue::from_static("ddns-rs (+github)"));
+        hdr.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        Ok(Self {
+            zone_name: zone.to_owned(),
+            record_name: record.to_owned(),
+            rtype: rtype.parse()?,
+            client: Client::builder().default_headers(hdr).build()?,
+        })
+    }
+
+    /*──────── tiny HTTP wrapper ────────*/
+
+    fn rrset_url(zone: &str, name: &str, typ: RecordType) -> String {
+        let name = if name.is_empty() { "@" } else { name };
+        format!("{API_ROOT}/domains/{zone}/records/{name}/{typ}")
+    }
+
+    async fn check(&self, resp: Response) -> Result<Value, ProviderError> {
+        let status = resp.status();
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        // DELETE answers 204 without a body
+        let v: Value = resp.json().await.unwrap_or(Value::Null);
+        if status.is_success() {
+            return Ok(v);
+        }
+        // `{"code":…,"message":…,"cause":…}`; `errors` lists field problems
+        let mut msg = v["message"]
+            .as_str()
+            .map(str::to_owned)
+            .unwrap_or_else(|| format!("HTTP {status}"));
+        for e in v["errors"].as_array().into_iter().flatten() {
+            if let Some(d) = e["description"].as_str() {
+                msg = format!("{msg}; {d}");
+            }
+        }
+        Err(ProviderError::from_status(status, msg, retry_after))
+    }
+
+    /// The rrset `name` / `typ`, `None` when it does not exist.
+    async fn rrset(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<Value>, ProviderError> {
+        let resp = self
+            .client
+            .get(Self::rrset_url(zone, name, typ))
+            .send()
+            .await?;
+        match self.check(resp).await {
+            Ok(v) => Ok(Some(v)),
+            Err(ProviderError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct GandiCfg {
+    token: String,
+}
+
+/// Builds `kind = "gandi"` entries; expects `token`.
+pub struct GandiFactory;
+
+impl ProviderFactory for GandiFactory {
+    fn kind(&self) -> &'static str {
+        "gandi"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: GandiCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(GandiProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            &c.token,
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for GandiProvider {
+    fn name(&self) -> &'static str {
+        "Gandi"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        Ok(self
+            .rrset(zone, name, typ)
+            .await?
+            .and_then(|s| s["rrset_values"][0].as_str().map(str::to_owned)))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        let resp = self
+            .client
+            .get(format!("{API_ROOT}/domains/{zone}/records"))
+            .send()
+            .await?;
+        let v = self.check(resp).await?;
+        let mut out = Vec::new();
+        for s in v.as_array().into_iter().flatten() {
+            let (Some(name), Some(Ok(typ))) = (
+                s["rrset_name"].as_str(),
+                s["rrset_type"].as_str().map(str::parse::<RecordType>),
+            ) else {
+                continue;
+            };
+            for value in s["rrset_values"].as_array().into_iter().flatten() {
+                out.push(DnsRecord {
+                    id: format!("{name}/{typ}"),
+                    name: name.to_owned(),
+                    typ,
+                    value: value.as_str().unwrap_or_default().to_owned(),
+                    ttl: s["rrset_ttl"].as_u64().unwrap_or_default() as u32,
+                });
+            }
+        }
+        Ok(out)
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        let resp = self
+            .client
+            .delete(Self::rrset_url(zone, name, typ))
+            .send()
+            .await?;
+        self.check(resp).await?;
+        info!("Gandi deleted {typ} {name}.{zone}");
+        Ok(())
+    }
+
+    async fn upsert_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+        ip: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        let resp = self
+            .client
+            .put(Self::rrset_url(zone, name, typ))
+            .json(&json!({
+                "rrset_values": [record_value(typ, ip)],
+                "rrset_ttl":    ttl.max(MIN_TTL),
+            }))
+            .send()
+            .await?;
+        self.check(resp).await?;
+        debug!("Gandi upsert {name}.{zone} -> {ip}");
+        Ok(())
+    }
+}
+
+/*──────── optional integration test (ignored) ────────*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn live_upsert() {
+        let token = env::var("GANDI_PAT").expect("GANDI_PAT not set");
+        let p = GandiProvider::new("example.com", "test-ddns", "A", &token).unwrap();
+        p.upsert_record("example.com", "test-ddns", RecordType::A, "1.1.1.1", 300)
+            .await
+            .unwrap();
+    }
+}
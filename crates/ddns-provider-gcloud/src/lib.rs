@@ -0,0 +1,445 @@
+//! Google Cloud DNS provider
+//!
+//! * Replaces `A` / `AAAA` / `TXT` / `CNAME` / `HTTPS` / `SVCB` rrsets through
+//!   the `changes` endpoint (delete the live set, add the new one, atomically).
+//! * Auth with a **service-account JSON key**: a signed JWT is exchanged for an
+//!   OAuth access token, which is cached until it expires.
+//! * The managed zone is found by DNS name (public zones first) and cached;
+//!   `managed_zone` (zone name or numeric id) skips the lookup.
+//! * API errors are mapped to [`ddns_provider::ProviderError`] by `reason`; a
+//!   change that raced another writer is transient and re-read on retry.
+
+mod auth;
+
+use async_trait::async_trait;
+use auth::{ServiceAccount, TokenSource};
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderCache, ProviderError, ProviderFactory, ProviderSpec,
+    ProviderTable, RecordType,
+    name::{absolute, relative},
+    parse_table, record_value,
+};
+use once_cell::sync::OnceCell;
+use reqwest::{Client, Method, StatusCode};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use std::{sync::Arc, time::Duration};
+use tracing::{debug, info};
+
+const API_ROOT: &str = "https://dns.googleapis.com/dns/v1";
+const DEFAULT_TTL: u32 = 300;
+
+/// Map a Cloud DNS error (`errors[0].reason`) to a [`ProviderError`].
+fn map_error(
+    status: StatusCode,
+    reason: &str,
+    msg: &str,
+    retry_after: Option<Duration>,
+) -> ProviderError {
+    let text = format!("{reason}: {msg}");
+    match reason {
+        "rateLimitExceeded" | "userRateLimitExceeded" | "quotaExceeded" => {
+            ProviderError::RateLimited { retry_after }
+        }
+        // the rrset changed between our read and the change request
+        "conditionNotMet" | "alreadyExists" => ProviderError::Transient(text),
+        "notFound" => ProviderError::NotFound(text),
+        "forbidden" | "insufficientPermissions" | "authError" => ProviderError::Auth(text),
+        _ => ProviderError::from_status(status, text, retry_after),
+    }
+}
+
+/*──────── JSON replies ────────*/
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ZoneList {
+    #[serde(default)]
+    managed_zones: Vec<ManagedZone>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManagedZone {
+    name: String,
+    #[serde(default)]
+    visibility: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RrSetList {
+    #[serde(default)]
+    rrsets: Vec<RrSet>,
+    #[serde(default)]
+    next_page_token: Option<String>,
+}
+
+/// A resource record set; deletions have to repeat it exactly.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+struct RrSet {
+    name: String,
+    #[serde(rename = "type")]
+    typ: String,
+    #[serde(default)]
+    ttl: u32,
+    #[serde(default)]
+    rrdatas: Vec<String>,
+}
+
+fn parse<T: DeserializeOwned>(v: Value) -> Result<T, ProviderError> {
+    serde_json::from_value(v).map_err(|e| ProviderError::Api(format!("bad Cloud DNS reply: {e}")))
+}
+
+/*──────── provider struct ────────*/
+
+pub struct GcloudProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    project: String,
+    auth: TokenSource,
+    client: Client,
+
+    zone_id: OnceCell<String>,
+}
+
+impl GcloudProvider {
+    /// `credentials` is a key-file path, `credentials_json` the key itself;
+    /// `project` defaults to the key's `project_id`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        zone: &str,
+        record: &str,
+        record_type: &str,
+        credentials: Option<&str>,
+        credentials_json: Option<&str>,
+        project: Option<&str>,
+        managed_zone: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let account = ServiceAccount::load(credentials, credentials_json)?;
+        let project = project
+            .or(account.project_id())
+            .ok_or_else(|| anyhow::anyhow!("gcloud: no `project` and none in the key file"))?
+            .to_owned();
+        let zone_id = OnceCell::new();
+        if let Some(id) = managed_zone {
+            let _ = zone_id.set(id.to_owned());
+        }
+        Ok(Self {
+            zone_name: zone.trim_end_matches('.').to_owned(),
+            record_name: record.to_owned(),
+            rtype: record_type.parse()?,
+            project,
+            auth: TokenSource::new(account),
+            client: Client::new(),
+            zone_id,
+        })
+    }
+
+    /*──────── authorized request helper ────────*/
+
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<Value>,
+    ) -> Result<Value, ProviderError> {
+        let token = self.auth.token(&self.client).await?;
+        let mut req = self
+            .client
+            .request(
+                method,
+                format!("{API_ROOT}/projects/{}{path}", self.project),
+            )
+            .bearer_auth(token)
+            .query(query);
+        if let Some(b) = body {
+            req = req.json(&b);
+        }
+        let resp = req.send().await?;
+        let status = resp.status();
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        // throttled / gateway responses are not always JSON
+        let v: Value = resp.json().await.unwrap_or(Value::Null);
+        if status.is_success() {
+            return Ok(v);
+        }
+        let err = &v["error"];
+        let msg = err["message"]
+            .as_str()
+            .map(str::to_owned)
+            .unwrap_or_else(|| format!("HTTP {status}"));
+        let reason = err["errors"][0]["reason"].as_str().unwrap_or("error");
+        Err(map_error(status, reason, &msg, retry_after))
+    }
+
+    /*──────── zone / record helpers ────────*/
+
+    /// Managed-zone name for `zone`; only the configured zone is cached.
+    async fn zone_id_for(&self, zone: &str) -> Result<String, ProviderError> {
+        let own = zone.trim_end_matches('.') == self.zone_name;
+        if own && let Some(id) = self.zone_id.get() {
+            return Ok(id.clone());
+        }
+        let dns_name = absolute("@", zone);
+        let list: ZoneList = parse(
+            self.send(
+                Method::GET,
+                "/managedZones",
+                &[("dnsName", &dns_name)],
+                None,
+            )
+            .await?,
+        )?;
+        // a split-horizon setup has a private zone of the same name
+        let z = list
+            .managed_zones
+            .iter()
+            .find(|z| z.visibility.as_deref().unwrap_or("public") == "public")
+            .or(list.managed_zones.first())
+            .ok_or_else(|| ProviderError::NotFound(format!("managed zone {dns_name}")))?;
+        if own {
+            let _ = self.zone_id.set(z.name.clone());
+        }
+        Ok(z.name.clone())
+    }
+
+    /// One page of rrsets, optionally filtered by name and type.
+    async fn rrsets(
+        &self,
+        zone_id: &str,
+        filter: Option<(&str, RecordType)>,
+        page_token: Option<&str>,
+    ) -> Result<RrSetList, ProviderError> {
+        let mut q = Vec::new();
+        if let Some((name, typ)) = filter {
+            q.push(("name", name));
+            q.push(("type", typ.as_str()));
+        }
+        if let Some(t) = page_token {
+            q.push(("pageToken", t));
+        }
+        parse(
+            self.send(
+                Method::GET,
+                &format!("/managedZones/{zone_id}/rrsets"),
+                &q,
+                None,
+            )
+            .await?,
+        )
+    }
+
+    /// The rrset `fqdn` / `typ`, if present.
+    async fn find_set(
+        &self,
+        zone_id: &str,
+        fqdn: &str,
+        typ: RecordType,
+    ) -> Result<Option<RrSet>, ProviderError> {
+        Ok(self
+            .rrsets(zone_id, Some((fqdn, typ)), None)
+            .await?
+            .rrsets
+            .into_iter()
+            .find(|s| s.name.eq_ignore_ascii_case(fqdn) && s.typ == typ.as_str()))
+    }
+
+    async fn change(
+        &self,
+        zone_id: &str,
+        deletions: &[RrSet],
+        additions: &[RrSet],
+    ) -> Result<(), ProviderError> {
+        let v = self
+            .send(
+                Method::POST,
+                &format!("/managedZones/{zone_id}/changes"),
+                &[],
+                Some(json!({ "deletions": deletions, "additions": additions })),
+            )
+            .await?;
+        debug!(
+            "Cloud DNS change {} {}",
+            v["id"].as_str().unwrap_or("?"),
+            v["status"].as_str().unwrap_or("?")
+        );
+        Ok(())
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct GcloudCfg {
+    #[serde(default)]
+    credentials: Option<String>,
+    #[serde(default)]
+    credentials_json: Option<String>,
+    #[serde(default)]
+    project: Option<String>,
+    #[serde(default)]
+    managed_zone: Option<String>,
+}
+
+/// Builds `kind = "gcloud"` entries; all keys are optional (`credentials` or
+/// `credentials_json`, `project`, `managed_zone`).
+pub struct GcloudFactory;
+
+impl ProviderFactory for GcloudFactory {
+    fn kind(&self) -> &'static str {
+        "gcloud"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: GcloudCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(GcloudProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            c.credentials.as_deref(),
+            c.credentials_json.as_deref(),
+            c.project.as_deref(),
+            c.managed_zone.as_deref(),
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for GcloudProvider {
+    fn name(&self) -> &'static str {
+        "CloudDNS"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    fn cache(&self) -> ProviderCache {
+        let mut c = ProviderCache::new();
+        if let Some(id) = self.zone_id.get() {
+            c.insert("zone_id".into(), id.clone());
+        }
+        c
+    }
+
+    fn restore_cache(&self, cache: &ProviderCache) {
+        if let Some(id) = cache.get("zone_id") {
+            let _ = self.zone_id.set(id.clone());
+        }
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        let set = self.find_set(&zid, &absolute(name, zone), typ).await?;
+        Ok(set.and_then(|s| s.rrdatas.into_iter().next()))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        let mut out = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let page = self.rrsets(&zid, None, token.as_deref()).await?;
+            for s in &page.rrsets {
+                let Ok(typ) = s.typ.parse::<RecordType>() else {
+                    continue;
+                };
+                for v in &s.rrdatas {
+                    out.push(DnsRecord {
+                        id: format!("{}/{}", s.name, s.typ),
+                        name: relative(&s.name, zone),
+                        typ,
+                        value: v.clone(),
+                        ttl: s.ttl,
+                    });
+                }
+            }
+            match page.next_page_token {
+                Some(t) if !t.is_empty() => token = Some(t),
+                _ => break,
+            }
+        }
+        Ok(out)
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        let fqdn = absolute(name, zone);
+        let set = self
+            .find_set(&zid, &fqdn, typ)
+            .await?
+            .ok_or_else(|| ProviderError::NotFound(format!("{typ} {fqdn}")))?;
+        self.change(&zid, &[set], &[]).await?;
+        info!("Cloud DNS deleted {typ} {fqdn}");
+        Ok(())
+    }
+
+    async fn upsert_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+        ip: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        let fqdn = absolute(name, zone);
+        let live = self.find_set(&zid, &fqdn, typ).await?;
+        let want = RrSet {
+            name: fqdn.clone(),
+            typ: typ.as_str().to_owned(),
+            ttl: if ttl == 0 { DEFAULT_TTL } else { ttl },
+            rrdatas: vec![record_value(typ, ip)],
+        };
+        if live.as_ref() == Some(&want) {
+            debug!("Cloud DNS {fqdn} already {ip}");
+            return Ok(());
+        }
+        // replace the whole rrset: other values of the same type are dropped
+        self.change(&zid, live.as_slice(), &[want]).await?;
+        debug!("Cloud DNS upsert {fqdn} -> {ip}");
+        Ok(())
+    }
+}
+
+/*──────── optional live-test (ignored by default) ────────*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Uses the key in `GOOGLE_APPLICATION_CREDENTIALS`.
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn live_upsert() {
+        let gcp =
+            GcloudProvider::new("example.com", "test-ddns", "A", None, None, None, None).unwrap();
+
+        gcp.upsert_record("example.com", "test-ddns", RecordType::A, "1.1.1.1", 60)
+            .await
+            .unwrap();
+    }
+}
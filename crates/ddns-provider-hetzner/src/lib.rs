@@ -0,0 +1,418 @@
+//! Hetzner DNS provider
+//!
+//! * Supports `A` / `AAAA` / `TXT` / `CNAME` record *upsert* (create or update).
+//! * Auth via **DNS API token** (`Auth-API-Token` header).
+//! * `zone_id` and `record_id` are cached locally to reduce API calls.
+//! * Errors are mapped to [`ddns_provider::ProviderError`] by HTTP status
+//!   (auth / not-found / rate-limit / invalid input).
+//! * A cached `record_id` that vanished upstream is dropped and the record re-created.
+
+use async_trait::async_trait;
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderCache, ProviderError, ProviderFactory, ProviderSpec,
+    ProviderTable, RecordType, parse_table,
+};
+use once_cell::sync::OnceCell;
+use reqwest::{
+    Client, Response,
+    header::{CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue, USER_AGENT},
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::{Arc, RwLock};
+use tracing::{debug, info};
+
+const API_ROOT: &str = "https://dns.hetzner.com/api/v1";
+const AUTH_HEADER: HeaderName = HeaderName::from_static("auth-api-token");
+
+/*──────── provider struct ────────*/
+
+pub struct HetznerProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    client: Client,
+
+    zone_id: OnceCell<String>,
+    record_id: RwLock<Option<String>>,
+}
+
+impl HetznerProvider {
+    pub fn new(zone: &str, record: &str, rtype: &str, token: &str) -> anyhow::Result<Self> {
+        let rtype: RecordType = rtype.parse()?;
+        if matches!(rtype, RecordType::HTTPS | RecordType::SVCB) {
+            anyhow::bail!("hetzner: {rtype} records are not supported");
+        }
+        let mut hdr = HeaderMap::new();
+        hdr.insert(AUTH_HEADER, HeaderValue::from_str(token)?);
+        hdr.insert(USER_AGENT, HeaderValue::from_static("ddns-rs (+github)"));
+        hdr.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        Ok(Self {
+            zone_name: zone.to_owned(),
+            record_name: record.to_owned(),
+            rtype,
+            client: Client::builder().default_headers(hdr).build()?,
+            zone_id: OnceCell::new(),
+            record_id: RwLock::new(None),
+        })
+    }
+
+    /*──────── tiny HTTP wrapper ────────*/
+
+    async fn get(&self, path: &str) -> Result<Value, ProviderError> {
+        self.check(self.client.get(format!("{API_ROOT}{path}")).send().await?)
+            .await
+    }
+    async fn post(&self, path: &str, body: Value) -> Result<Value, ProviderError> {
+        self.check(
+            self.client
+                .post(format!("{API_ROOT}{path}"))
+                .json(&body)
+                .send()
+                .await?,
+        )
+        .await
+    }
+    async fn put(&self, path: &str, body: Value) -> Result<Value, ProviderError> {
+        self.check(
+            self.client
+                .put(format!("{API_ROOT}{path}"))
+                .json(&body)
+                .send()
+                .await?,
+        )
+        .await
+    }
+
+    async fn delete(&self, path: &str) -> Result<Value, ProviderError> {
+        self.check(
+            self.client
+                .delete(format!("{API_ROOT}{path}"))
+                .send()
+                .await?,
+        )
+        .await
+    }
+
+    async fn check(&self, resp: Response) -> Result<Value, ProviderError> {
+        let status = resp.status();
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        let v: Value = resp.json().await.unwrap_or(Value::Null);
+        if status.is_success() {
+            return Ok(v);
+        }
+        // `{"error":{"message":…,"code":…}}`, or a bare `{"message":…}` for 401
+        let msg = v["error"]["message"]
+            .as_str()
+            .or(v["message"].as_str())
+            .filter(|m| !m.is_empty())
+            .map(str::to_owned)
+            .unwrap_or_else(|| format!("HTTP {status}"));
+        Err(ProviderError::from_status(status, msg, retry_after))
+    }
+
+    /*──────── zone / record helpers ────────*/
+
+    async fn ensure_zone_id(&self) -> Result<&str, ProviderError> {
+        if let Some(id) = self.zone_id.get() {
+            return Ok(id);
+        }
+        let id = self.find_zone(&self.zone_name).await?;
+        let _ = self.zone_id.set(id);
+        Ok(self.zone_id.get().expect("zone_id set"))
+    }
+
+    /// Zone id for `zone`; the configured zone goes through the cache.
+    async fn zone_id_for(&self, zone: &str) -> Result<String, ProviderError> {
+        if zone == self.zone_name {
+            return self.ensure_zone_id().await.map(str::to_owned);
+        }
+        self.find_zone(zone).await
+    }
+
+    async fn find_zone(&self, zone: &str) -> Result<String, ProviderError> {
+        let v = match self.get(&format!("/zones?name={zone}")).await {
+            // unknown zone names are answered with 404
+            Err(ProviderError::NotFound(_)) => Value::Null,
+            res => res?,
+        };
+        v["zones"]
+            .get(0)
+            .and_then(|z| z["id"].as_str())
+            .map(str::to_owned)
+            .ok_or_else(|| ProviderError::NotFound(format!("zone {zone}")))
+    }
+
+    /// All records of a zone (the API cannot filter by name or type).
+    async fn records(&self, zid: &str) -> Result<Vec<Value>, ProviderError> {
+        let mut out = Vec::new();
+        let mut page = 1;
+        loop {
+            let v = self
+                .get(&format!("/records?zone_id={zid}&per_page=100&page={page}"))
+                .await?;
+            out.extend(v["records"].as_array().into_iter().flatten().cloned());
+            let last = v["meta"]["pagination"]["last_page"].as_u64().unwrap_or(1);
+            if page >= last {
+                break;
+            }
+            page += 1;
+        }
+        Ok(out)
+    }
+
+    fn cached_record_id(&self) -> Option<String> {
+        self.record_id.read().expect("record_id lock").clone()
+    }
+
+    fn set_record_id(&self, id: Option<String>) {
+        *self.record_id.write().expect("record_id lock") = id;
+    }
+
+    async fn ensure_record_id(&self) -> Result<Option<String>, ProviderError> {
+        if let Some(id) = self.cached_record_id() {
+            return Ok(Some(id));
+        }
+        self.lookup_record(&self.zone_name, &self.record_name, self.rtype)
+            .await?;
+        Ok(self.cached_record_id())
+    }
+
+    /// Fetch the record by name/type; caches its id when it is the
+    /// configured one.
+    async fn lookup_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<Value>, ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        let rec =
+            self.records(&zid).await?.into_iter().find(|r| {
+                r["name"].as_str() == Some(name) && r["type"].as_str() == Some(typ.as_str())
+            });
+        if self.is_configured(zone, name, typ) {
+            self.set_record_id(
+                rec.as_ref()
+                    .and_then(|r| r["id"].as_str())
+                    .map(str::to_owned),
+            );
+        }
+        Ok(rec)
+    }
+
+    /*──────── create / update helpers ────────*/
+
+    /// `ttl` 0 leaves the record on the zone's default TTL.
+    fn record_body(&self, zid: &str, content: &str, ttl: u32) -> Value {
+        let mut body = json!({
+            "zone_id": zid,
+            "type":    self.rtype.as_str(),
+            "name":    self.record_name,
+            "value":   content,
+        });
+        if ttl > 0 {
+            body["ttl"] = json!(ttl);
+        }
+        body
+    }
+
+    async fn create_record(&self, zid: &str, content: &str, ttl: u32) -> Result<(), ProviderError> {
+        let v = self
+            .post("/records", self.record_body(zid, content, ttl))
+            .await?;
+        let id = v["record"]["id"]
+            .as_str()
+            .ok_or_else(|| ProviderError::Api("create: missing id".into()))?;
+        self.set_record_id(Some(id.to_owned()));
+        info!("Hetzner created record id={id}");
+        Ok(())
+    }
+
+    async fn update_record(
+        &self,
+        zid: &str,
+        rid: &str,
+        content: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        self.put(
+            &format!("/records/{rid}"),
+            self.record_body(zid, content, ttl),
+        )
+        .await?;
+        info!("Hetzner updated record id={rid}");
+        Ok(())
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct HetznerCfg {
+    token: String,
+}
+
+/// Builds `kind = "hetzner"` entries; expects `token`.
+pub struct HetznerFactory;
+
+impl ProviderFactory for HetznerFactory {
+    fn kind(&self) -> &'static str {
+        "hetzner"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: HetznerCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(HetznerProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            &c.token,
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for HetznerProvider {
+    fn name(&self) -> &'static str {
+        "Hetzner"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    fn cache(&self) -> ProviderCache {
+        let mut c = ProviderCache::new();
+        if let Some(id) = self.zone_id.get() {
+            c.insert("zone_id".into(), id.clone());
+        }
+        if let Some(id) = self.cached_record_id() {
+            c.insert("record_id".into(), id);
+        }
+        c
+    }
+
+    fn restore_cache(&self, cache: &ProviderCache) {
+        if let Some(id) = cache.get("zone_id") {
+            let _ = self.zone_id.set(id.clone());
+        }
+        if let Some(id) = cache.get("record_id") {
+            self.set_record_id(Some(id.clone()));
+        }
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        Ok(self
+            .lookup_record(zone, name, typ)
+            .await?
+            .and_then(|r| r["value"].as_str().map(str::to_owned)))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        Ok(self
+            .records(&zid)
+            .await?
+            .iter()
+            .filter_map(|r| {
+                Some(DnsRecord {
+                    id: r["id"].as_str()?.to_owned(),
+                    name: r["name"].as_str().unwrap_or("@").to_owned(),
+                    typ: r["type"].as_str()?.parse().ok()?,
+                    value: r["value"].as_str().unwrap_or_default().to_owned(),
+                    ttl: r["ttl"].as_u64().unwrap_or_default() as u32,
+                })
+            })
+            .collect())
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        let ids: Vec<String> = self
+            .records(&zid)
+            .await?
+            .iter()
+            .filter(|r| {
+                r["name"].as_str() == Some(name) && r["type"].as_str() == Some(typ.as_str())
+            })
+            .filter_map(|r| r["id"].as_str().map(str::to_owned))
+            .collect();
+        if ids.is_empty() {
+            return Err(ProviderError::NotFound(format!("{typ} {name}.{zone}")));
+        }
+        for rid in ids {
+            self.delete(&format!("/records/{rid}")).await?;
+            info!("Hetzner deleted record id={rid}");
+            if self.cached_record_id().as_deref() == Some(rid.as_str()) {
+                self.set_record_id(None);
+            }
+        }
+        Ok(())
+    }
+
+    async fn upsert_record(
+        &self,
+        _zone: &str,
+        _name: &str,
+        _typ: RecordType,
+        ip: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        let zid = self.ensure_zone_id().await?;
+        match self.ensure_record_id().await? {
+            Some(rid) => match self.update_record(zid, &rid, ip, ttl).await {
+                Err(ProviderError::NotFound(_)) => {
+                    self.set_record_id(None);
+                    self.create_record(zid, ip, ttl).await
+                }
+                res => res,
+            },
+            None => self.create_record(zid, ip, ttl).await,
+        }?;
+        debug!(
+            "Hetzner upsert {}.{} -> {}",
+            self.record_name, self.zone_name, ip
+        );
+        Ok(())
+    }
+}
+
+/*──────── optional integration test (ignored) ────────*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn live_upsert() {
+        let token = env::var("HETZNER_DNS_TOKEN").expect("HETZNER_DNS_TOKEN not set");
+        let p = HetznerProvider::new("example.com", "test-ddns", "A", &token).unwrap();
+        p.upsert_record("example.com", "test-ddns", RecordType::A, "1.1.1.1", 60)
+            .await
+            .unwrap();
+    }
+}
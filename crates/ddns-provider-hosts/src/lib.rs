@@ -0,0 +1,663 @@
+//! Hosts-file provider
+//!
+//! * Keeps `A` / `AAAA` entries in a marked block of a hosts-format file
+//!   (`/etc/hosts`, a dnsmasq `addn-hosts` file, Pi-hole's `custom.list`):
+//!   `# BEGIN <block>` … `# END <block>`.
+//! * Lines outside the block are never touched; the block is appended when
+//!   missing. Several entries (A + AAAA, other names) share one block.
+//! * Written atomically (`<path>.tmp` + rename, file mode preserved); falls
+//!   back to an in-place write when the file cannot be replaced (a
+//!   bind-mounted `/etc/hosts` in a container).
+//! * With `pidfile`, the process named there gets a `SIGHUP` after each
+//!   change (dnsmasq re-reads its hosts files on it). While a signal is
+//!   owed, the record reads as missing so the next cycle writes again and
+//!   repeats it.
+
+use async_trait::async_trait;
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderError, ProviderFactory, ProviderSpec, ProviderTable,
+    RecordType,
+    file::{EDIT_LOCK, io_error, write_atomic},
+    name::fqdn,
+    parse_table,
+};
+use serde::Deserialize;
+use std::{
+    fs, io,
+    net::IpAddr,
+    path::PathBuf,
+    process::Stdio,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use tokio::{process::Command, time::timeout};
+use tracing::{debug, info};
+
+const DEFAULT_PATH: &str = "/etc/hosts";
+const DEFAULT_BLOCK: &str = "ddns-rs";
+const SIGNAL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/*──────── block editing ────────*/
+
+/// Address family of a hosts line's address.
+fn is_v6(addr: &str) -> bool {
+    addr.contains(':')
+}
+
+/// Line index range of the block's content (between the markers).
+fn find_block(lines: &[&str], block: &str) -> Result<Option<(usize, usize)>, ProviderError> {
+    let begin = format!("# BEGIN {block}");
+    let end = format!("# END {block}");
+    let Some(b) = lines.iter().position(|l| l.trim() == begin) else {
+        return Ok(None);
+    };
+    match lines[b + 1..].iter().position(|l| l.trim() == end) {
+        Some(e) => Ok(Some((b + 1, b + 1 + e))),
+        None => Err(ProviderError::InvalidInput(format!(
+            "`{begin}` without `{end}`"
+        ))),
+    }
+}
+
+/// `(address, names)` of a hosts line, `None` for blanks and comments.
+fn parse_line(line: &str) -> Option<(&str, Vec<&str>)> {
+    let line = line.split('#').next()?;
+    let mut fields = line.split_whitespace();
+    let addr = fields.next()?;
+    Some((addr, fields.collect()))
+}
+
+/// `(address, names)` of every entry in the block.
+fn block_entries<'a>(
+    text: &'a str,
+    block: &str,
+) -> Result<Vec<(&'a str, Vec<&'a str>)>, ProviderError> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let Some((from, to)) = find_block(&lines, block)? else {
+        return Ok(Vec::new());
+    };
+    Ok(lines[from..to]
+        .iter()
+        .filter_map(|l| parse_line(l))
+        .collect())
+}
+
+/// Make `line` the only entry for `host` in `v6`'s family inside the block
+/// (`None` removes it). Returns the new text and whether it changed.
+fn edit_block(
+    text: &str,
+    block: &str,
+    host: &str,
+    v6: bool,
+    line: Option<&str>,
+) -> Result<(String, bool), ProviderError> {
+    let mut lines: Vec<&str> = text.split('\n').collect();
+    let (from, to) = match find_block(&lines, block)? {
+        Some(r) => r,
+        None if line.is_none() => return Ok((text.to_owned(), false)),
+        None => {
+            // append the block, keeping a trailing newline
+            let at = match lines.last() {
+                Some(&"") => lines.len() - 1,
+                _ => lines.len(),
+            };
+            let begin = format!("# BEGIN {block}");
+            let end = format!("# END {block}");
+            let mut out: Vec<&str> = lines[..at].to_vec();
+            out.extend([begin.as_str(), end.as_str()]);
+            out.extend(&lines[at..]);
+            if at == lines.len() {
+                out.push("");
+            }
+            let text = out.join("\n");
+            return edit_block(&text, block, host, v6, line);
+        }
+    };
+
+    let matches = |l: &str| {
+        parse_line(l).is_some_and(|(addr, names)| {
+            is_v6(addr) == v6 && names.first().is_some_and(|n| n.eq_ignore_ascii_case(host))
+        })
+    };
+    let hits: Vec<usize> = (from..to).filter(|&i| matches(lines[i])).collect();
+    if let (Some(new), [only]) = (line, hits.as_slice())
+        && lines[*only] == new
+    {
+        return Ok((text.to_owned(), false));
+    }
+    if line.is_none() && hits.is_empty() {
+        return Ok((text.to_owned(), false));
+    }
+
+    match (line, hits.first()) {
+        (Some(new), Some(&first)) => lines[first] = new,
+        (Some(new), None) => lines.insert(to, new),
+        (None, _) => {}
+    }
+    // drop the remaining (or, when removing, all) hits; later ones first
+    let skip = usize::from(line.is_some());
+    for &i in hits.iter().skip(skip).rev() {
+        lines.remove(i);
+    }
+    Ok((lines.join("\n"), true))
+}
+
+/*──────── provider struct ────────*/
+
+pub struct HostsProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    path: PathBuf,
+    block: String,
+    aliases: Vec<String>,
+    pidfile: Option<PathBuf>,
+
+    /// the last SIGHUP failed; retry it even if the file is unchanged
+    signal_pending: AtomicBool,
+}
+
+impl HostsProvider {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        zone: &str,
+        record: &str,
+        rtype: &str,
+        path: impl Into<PathBuf>,
+        block: &str,
+        aliases: Vec<String>,
+        pidfile: Option<PathBuf>,
+    ) -> anyhow::Result<Self> {
+        let rtype: RecordType = rtype.parse()?;
+        if !matches!(rtype, RecordType::A | RecordType::AAAA) {
+            anyhow::bail!("hosts: only A / AAAA records are supported, not {rtype}");
+        }
+        if block.trim().is_empty() || block.contains('\n') {
+            anyhow::bail!("hosts: `block` must be a non-empty single line");
+        }
+        Ok(Self {
+            zone_name: zone.trim_end_matches('.').to_owned(),
+            record_name: record.to_owned(),
+            rtype,
+            path: path.into(),
+            block: block.trim().to_owned(),
+            aliases,
+            pidfile,
+            signal_pending: AtomicBool::new(false),
+        })
+    }
+
+    fn read(&self) -> Result<String, ProviderError> {
+        fs::read_to_string(&self.path).map_err(|e| io_error(&self.path, e))
+    }
+
+    /// Apply [`edit_block`] under the lock and write the file on change.
+    fn modify(&self, host: &str, v6: bool, line: Option<&str>) -> Result<bool, ProviderError> {
+        let _guard = EDIT_LOCK.lock().expect("edit lock");
+        // a missing file is created with just the block
+        let text = match fs::read_to_string(&self.path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound && line.is_some() => String::new(),
+            res => res.map_err(|e| io_error(&self.path, e))?,
+        };
+        let (text, changed) = edit_block(&text, &self.block, host, v6, line)?;
+        if changed {
+            write_atomic(&self.path, &text, true).map_err(|e| io_error(&self.path, e))?;
+            info!("Hosts wrote `{}`", self.path.display());
+        }
+        Ok(changed)
+    }
+
+    /// `SIGHUP` the process in `pidfile` (if any) after a change, or to
+    /// retry a failed signal.
+    async fn signal(&self, changed: bool) -> Result<(), ProviderError> {
+        let Some(pidfile) = &self.pidfile else {
+            return Ok(());
+        };
+        if !changed && !self.signal_pending.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        self.signal_pending.store(true, Ordering::Relaxed);
+        let raw = fs::read_to_string(pidfile).map_err(|e| {
+            // the daemon may just be restarting
+            ProviderError::Transient(format!("`{}`: {e}", pidfile.display()))
+        })?;
+        let pid: u32 = raw.trim().parse().map_err(|_| {
+            ProviderError::InvalidInput(format!(
+                "`{}`: no pid in `{}`",
+                pidfile.display(),
+                raw.trim()
+            ))
+        })?;
+
+        let child = Command::new("kill")
+            .args(["-s", "HUP", &pid.to_string()])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| ProviderError::InvalidInput(format!("spawn `kill`: {e}")))?;
+        let out = match timeout(SIGNAL_TIMEOUT, child.wait_with_output()).await {
+            Ok(res) => res.map_err(|e| ProviderError::Transient(e.to_string()))?,
+            Err(_) => return Err(ProviderError::Transient("`kill` timed out".into())),
+        };
+        if !out.status.success() {
+            let stderr = String::from_utf8_lossy(&out.stderr).trim().to_owned();
+            return Err(ProviderError::Transient(format!("SIGHUP {pid}: {stderr}")));
+        }
+        self.signal_pending.store(false, Ordering::Relaxed);
+        debug!("Hosts sent SIGHUP to {pid}");
+        Ok(())
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct HostsCfg {
+    #[serde(default = "default_path")]
+    path: PathBuf,
+    #[serde(default = "default_block")]
+    block: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    pidfile: Option<PathBuf>,
+}
+
+fn default_path() -> PathBuf {
+    DEFAULT_PATH.into()
+}
+
+fn default_block() -> String {
+    DEFAULT_BLOCK.into()
+}
+
+/// Builds `kind = "hosts"` entries; optionally takes `path`, `block`,
+/// `aliases` and `pidfile`.
+pub struct HostsFactory;
+
+impl ProviderFactory for HostsFactory {
+    fn kind(&self) -> &'static str {
+        "hosts"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: HostsCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(HostsProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            c.path,
+            &c.block,
+            c.aliases,
+            c.pidfile,
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for HostsProvider {
+    fn name(&self) -> &'static str {
+        "Hosts"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        let text = match self.read() {
+            Err(ProviderError::NotFound(_)) => return Ok(None),
+            res => res?,
+        };
+        // the file may be current while the daemon still serves the old data
+        if self.signal_pending.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let host = fqdn(name, zone);
+        let Some((addr, names)) =
+            block_entries(&text, &self.block)?
+                .into_iter()
+                .find(|(addr, names)| {
+                    is_v6(addr) == (typ == RecordType::AAAA)
+                        && names.first().is_some_and(|n| n.eq_ignore_ascii_case(&host))
+                })
+        else {
+            return Ok(None);
+        };
+        // changed `aliases` need the line rewritten too
+        if names[1..] != self.aliases {
+            return Ok(None);
+        }
+        Ok(Some(addr.to_owned()))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        let text = self.read()?;
+        let zone = zone.trim_end_matches('.');
+        let suffix = format!(".{zone}");
+        Ok(block_entries(&text, &self.block)?
+            .into_iter()
+            .filter_map(|(addr, names)| {
+                let host = names.first()?;
+                let name = if host.eq_ignore_ascii_case(zone) {
+                    "@"
+                } else {
+                    host.strip_suffix(&suffix)?
+                };
+                let typ = if is_v6(addr) {
+                    RecordType::AAAA
+                } else {
+                    RecordType::A
+                };
+                Some(DnsRecord {
+                    id: format!("{name}/{typ}"),
+                    name: name.to_owned(),
+                    typ,
+                    value: addr.to_owned(),
+                    ttl: 0,
+                })
+            })
+            .collect())
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        let host = fqdn(name, zone);
+        if !self.modify(&host, typ == RecordType::AAAA, None)? {
+            return Err(ProviderError::NotFound(format!("{typ} {host}")));
+        }
+        self.signal(true).await?;
+        info!("Hosts deleted {typ} {host}");
+        Ok(())
+    }
+
+    async fn upsert_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+        ip: &str,
+        _ttl: u32,
+    ) -> Result<(), ProviderError> {
+        // the line goes into the file verbatim; anything else would corrupt it
+        let valid = ip
+            .parse::<IpAddr>()
+            .is_ok_and(|a| a.is_ipv6() == (typ == RecordType::AAAA));
+        if !valid {
+            return Err(ProviderError::InvalidInput(format!(
+                "`{ip}` is not a {typ} address"
+            )));
+        }
+        let host = fqdn(name, zone);
+        let mut line = format!("{ip}\t{host}");
+        for a in &self.aliases {
+            line = format!("{line} {a}");
+        }
+        let changed = self.modify(&host, typ == RecordType::AAAA, Some(&line))?;
+        self.signal(changed).await?;
+        debug!("Hosts upsert {host} -> {ip} (changed: {changed})");
+        Ok(())
+    }
+}
+
+/*──────── local tests (temp dir) ────────*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    const HOSTS: &str = "\
+127.0.0.1\tlocalhost
+# BEGIN ddns-rs
+192.0.2.1\tother.example.com
+# END ddns-rs
+10.0.0.1\tprinter
+";
+
+    #[test]
+    fn block_edits() {
+        let (t, changed) = edit_block(
+            HOSTS,
+            "ddns-rs",
+            "home.example.com",
+            false,
+            Some("192.0.2.7\thome.example.com"),
+        )
+        .unwrap();
+        assert!(changed);
+        assert_eq!(
+            t,
+            "127.0.0.1\tlocalhost\n# BEGIN ddns-rs\n192.0.2.1\tother.example.com\n\
+             192.0.2.7\thome.example.com\n# END ddns-rs\n10.0.0.1\tprinter\n"
+        );
+        // same line again → untouched; the other family is separate
+        let (t2, changed) = edit_block(
+            &t,
+            "ddns-rs",
+            "home.example.com",
+            false,
+            Some("192.0.2.7\thome.example.com"),
+        )
+        .unwrap();
+        assert!(!changed);
+        let (t2, _) = edit_block(
+            &t2,
+            "ddns-rs",
+            "home.example.com",
+            true,
+            Some("2001:db8::7\thome.example.com"),
+        )
+        .unwrap();
+        let (t2, changed) = edit_block(&t2, "ddns-rs", "home.example.com", false, None).unwrap();
+        assert!(changed);
+        assert!(t2.contains("2001:db8::7\thome.example.com\n# END"));
+        assert!(!t2.contains("192.0.2.7"));
+
+        // no block yet → appended, content outside unchanged
+        let (t3, _) = edit_block(
+            "127.0.0.1 localhost",
+            "lan",
+            "a.b",
+            false,
+            Some("1.2.3.4\ta.b"),
+        )
+        .unwrap();
+        assert_eq!(
+            t3,
+            "127.0.0.1 localhost\n# BEGIN lan\n1.2.3.4\ta.b\n# END lan\n"
+        );
+
+        assert!(edit_block("# BEGIN x\n", "x", "a", false, Some("1.2.3.4\ta")).is_err());
+    }
+
+    #[tokio::test]
+    async fn upsert_rejects_bad_addresses() {
+        let dir = env::temp_dir().join(format!("ddns-hosts-bad-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hosts");
+        fs::write(&path, HOSTS).unwrap();
+
+        let p = HostsProvider::new(
+            "example.com",
+            "home",
+            "A",
+            &path,
+            DEFAULT_BLOCK,
+            vec![],
+            None,
+        )
+        .unwrap();
+        for (typ, ip) in [
+            (RecordType::A, "2001:db8::1"),
+            (RecordType::AAAA, "192.0.2.1"),
+            (RecordType::A, "not-an-ip"),
+            (RecordType::A, "192.0.2.1 evil.example"),
+            (RecordType::AAAA, "::1\n127.0.0.1"),
+        ] {
+            let err = p
+                .upsert_record("example.com", "home", typ, ip, 0)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, ProviderError::InvalidInput(_)), "{ip}: {err}");
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), HOSTS);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn upsert_keeps_outside_lines() {
+        let dir = env::temp_dir().join(format!("ddns-hosts-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hosts");
+        fs::write(&path, HOSTS).unwrap();
+
+        let p = HostsProvider::new(
+            "example.com",
+            "other",
+            "A",
+            &path,
+            DEFAULT_BLOCK,
+            vec!["other".into()],
+            None,
+        )
+        .unwrap();
+        p.upsert_record("example.com", "other", RecordType::A, "192.0.2.9", 0)
+            .await
+            .unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with(
+            "127.0.0.1\tlocalhost\n# BEGIN ddns-rs\n192.0.2.9\tother.example.com other\n"
+        ));
+        assert!(text.ends_with("# END ddns-rs\n10.0.0.1\tprinter\n"));
+        assert_eq!(
+            p.get_record("example.com", "other", RecordType::A)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("192.0.2.9")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn alias_change_rewrites_line() {
+        let dir = env::temp_dir().join(format!("ddns-hosts-alias-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hosts");
+        fs::write(&path, HOSTS).unwrap();
+        let provider = |aliases: &[&str]| {
+            let aliases = aliases.iter().map(|a| a.to_string()).collect();
+            HostsProvider::new(
+                "example.com",
+                "home",
+                "A",
+                &path,
+                DEFAULT_BLOCK,
+                aliases,
+                None,
+            )
+            .unwrap()
+        };
+        let set = "192.0.2.5";
+
+        let old = provider(&["home"]);
+        old.upsert_record("example.com", "home", RecordType::A, set, 0)
+            .await
+            .unwrap();
+        let live = old.get_record("example.com", "home", RecordType::A).await;
+        assert_eq!(live.unwrap().as_deref(), Some(set));
+
+        // new aliases in the config: not up to date until the line is rewritten
+        let new = provider(&["home", "nas"]);
+        let live = new.get_record("example.com", "home", RecordType::A).await;
+        assert_eq!(live.unwrap(), None);
+        new.upsert_record("example.com", "home", RecordType::A, set, 0)
+            .await
+            .unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("192.0.2.5\thome.example.com home nas\n"));
+        let live = new.get_record("example.com", "home", RecordType::A).await;
+        assert_eq!(live.unwrap().as_deref(), Some(set));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn failed_signal_is_retried_next_cycle() {
+        let dir = env::temp_dir().join(format!("ddns-hosts-hup-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (path, pidfile) = (dir.join("hosts"), dir.join("dnsmasq.pid"));
+        fs::write(&path, HOSTS).unwrap();
+        let p = HostsProvider::new(
+            "example.com",
+            "home",
+            "A",
+            &path,
+            DEFAULT_BLOCK,
+            vec![],
+            Some(pidfile.clone()),
+        )
+        .unwrap();
+
+        // no pidfile yet: the file is written but the signal fails
+        let err = p
+            .upsert_record("example.com", "home", RecordType::A, "192.0.2.5", 0)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProviderError::Transient(_)));
+        assert!(fs::read_to_string(&path).unwrap().contains("192.0.2.5"));
+
+        // next cycle: the scheduler's read must not report "up to date" …
+        let live = p.get_record("example.com", "home", RecordType::A).await;
+        assert_eq!(live.unwrap(), None);
+
+        // … so it writes again, which repeats the signal
+        let mut daemon = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        fs::write(&pidfile, daemon.id().to_string()).unwrap();
+        p.upsert_record("example.com", "home", RecordType::A, "192.0.2.5", 0)
+            .await
+            .unwrap();
+        // `sleep` does not handle SIGHUP, so it is gone now
+        assert!(!daemon.wait().unwrap().success());
+        assert_eq!(
+            p.get_record("example.com", "home", RecordType::A)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("192.0.2.5")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,568 @@
+//! Huawei Cloud DNS provider
+//!
+//! * Supports `A` / `AAAA` / `TXT` / `CNAME` record *upsert* (create or update)
+//!   in public zones through the v2 `recordsets` API.
+//! * Auth via **AK/SK**; every request is signed with `SDK-HMAC-SHA256`
+//!   (see [`sign`]), optionally scoped with `X-Project-Id`.
+//! * `region` selects the endpoint (`dns.<region>.myhuaweicloud.com`),
+//!   `endpoint` overrides it with a full URL.
+//! * The public `zone_id` and the `recordset_id` are cached locally; a cached
+//!   id that vanished upstream is dropped and the record set re-created.
+
+mod sign;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderCache, ProviderError, ProviderFactory, ProviderSpec,
+    ProviderTable, RecordType, name::absolute, parse_table, record_value,
+};
+use once_cell::sync::OnceCell;
+use reqwest::{Client, Method, StatusCode, Url, header::CONTENT_TYPE};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tracing::{debug, info};
+
+const DEFAULT_TTL: u32 = 300; // Huawei's default
+const PAGE_SIZE: u64 = 500;
+
+/// Map an error reply to a [`ProviderError`]. DNS errors carry
+/// `code`/`message`, API Gateway ones `error_code`/`error_msg`.
+fn map_error(
+    status: StatusCode,
+    code: &str,
+    msg: &str,
+    retry_after: Option<Duration>,
+) -> ProviderError {
+    let text = format!("{code}: {msg}");
+    match code {
+        // throttled by the gateway
+        "APIGW.0308" => ProviderError::RateLimited { retry_after },
+        // bad AK, signature, date or project (APIGW.01xx is a routing
+        // error, e.g. wrong endpoint, and falls through to the status)
+        c if c.starts_with("APIGW.03") => ProviderError::Auth(text),
+        _ => ProviderError::from_status(status, text, retry_after),
+    }
+}
+
+/*──────── provider struct ────────*/
+
+pub struct HuaweiProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    ak: String,
+    sk: String,
+    project_id: Option<String>,
+    api_root: String,
+    host: String,
+    client: Client,
+
+    zone_id: OnceCell<String>,
+    record_id: RwLock<Option<String>>,
+}
+
+impl HuaweiProvider {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        zone: &str,
+        record: &str,
+        rtype: &str,
+        access_key: &str,
+        secret_key: &str,
+        region: &str,
+        endpoint: Option<&str>,
+        project_id: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let rtype: RecordType = rtype.parse()?;
+        if matches!(rtype, RecordType::HTTPS | RecordType::SVCB) {
+            anyhow::bail!("huawei: {rtype} records are not supported");
+        }
+        let api_root = match endpoint {
+            Some(url) => url.trim_end_matches('/').to_owned(),
+            None => format!("https://dns.{region}.myhuaweicloud.com"),
+        };
+        let url = Url::parse(&api_root)?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(h), Some(p)) => format!("{h}:{p}"),
+            (Some(h), None) => h.to_owned(),
+            (None, _) => anyhow::bail!("huawei: endpoint `{api_root}` has no host"),
+        };
+        Ok(Self {
+            zone_name: zone.trim_end_matches('.').to_owned(),
+            record_name: record.to_owned(),
+            rtype,
+            ak: access_key.to_owned(),
+            sk: secret_key.to_owned(),
+            project_id: project_id.map(str::to_owned),
+            api_root,
+            host,
+            client: Client::new(),
+            zone_id: OnceCell::new(),
+            record_id: RwLock::new(None),
+        })
+    }
+
+    /*──────── signed request helper ────────*/
+
+    async fn call(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<Value>,
+    ) -> Result<Value, ProviderError> {
+        let body = body.map(|b| b.to_string()).unwrap_or_default();
+        let mut headers = vec![("content-type", "application/json")];
+        if let Some(p) = &self.project_id {
+            headers.push(("x-project-id", p));
+        }
+        let signed = sign::sign(
+            &self.ak,
+            &self.sk,
+            &sign::Request {
+                method: method.as_str(),
+                host: &self.host,
+                path,
+                query,
+                headers: &headers,
+                payload: body.as_bytes(),
+            },
+            Utc::now(),
+        );
+
+        let mut url = format!("{}{path}", self.api_root);
+        if !query.is_empty() {
+            url = format!("{url}?{}", sign::canonical_query(query));
+        }
+        let mut req = self
+            .client
+            .request(method, url)
+            .header(CONTENT_TYPE, "application/json");
+        if let Some(p) = &self.project_id {
+            req = req.header("X-Project-Id", p);
+        }
+        for (k, v) in signed {
+            req = req.header(k, v);
+        }
+        if !body.is_empty() {
+            req = req.body(body);
+        }
+
+        let resp = req.send().await?;
+        let status = resp.status();
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        // DELETE / PUT may answer without a body
+        let v: Value = resp.json().await.unwrap_or(Value::Null);
+        if status.is_success() {
+            return Ok(v);
+        }
+        let code = v["code"]
+            .as_str()
+            .or_else(|| v["error_code"].as_str())
+            .unwrap_or_default();
+        let msg = v["message"]
+            .as_str()
+            .or_else(|| v["error_msg"].as_str())
+            .map(str::to_owned)
+            .unwrap_or_else(|| format!("HTTP {status}"));
+        Err(map_error(status, code, &msg, retry_after))
+    }
+
+    /*──────── zone / record helpers ────────*/
+
+    /// Id of the public zone `zone`; the configured zone's id is cached.
+    async fn zone_id(&self, zone: &str) -> Result<String, ProviderError> {
+        let own = zone.trim_end_matches('.') == self.zone_name;
+        if own && let Some(id) = self.zone_id.get() {
+            return Ok(id.clone());
+        }
+        let name = absolute("@", zone);
+        let v = self
+            .call(
+                Method::GET,
+                "/v2/zones",
+                &[
+                    ("type", "public".into()),
+                    ("name", name.clone()),
+                    ("search_mode", "equal".into()),
+                ],
+                None,
+            )
+            .await?;
+        // older gateways ignore `search_mode` and match by substring
+        let id = v["zones"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|z| {
+                z["name"]
+                    .as_str()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(&name))
+            })
+            .and_then(|z| z["id"].as_str())
+            .ok_or_else(|| ProviderError::NotFound(format!("public zone {zone}")))?
+            .to_owned();
+        if own {
+            let _ = self.zone_id.set(id.clone());
+        }
+        Ok(id)
+    }
+
+    /// Record sets `name` / `typ` of the zone `zid`.
+    async fn find(
+        &self,
+        zid: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Vec<Value>, ProviderError> {
+        let v = self
+            .call(
+                Method::GET,
+                &format!("/v2/zones/{zid}/recordsets"),
+                &[
+                    ("name", name.to_owned()),
+                    ("type", typ.as_str().into()),
+                    ("search_mode", "equal".into()),
+                ],
+                None,
+            )
+            .await?;
+        Ok(v["recordsets"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|s| {
+                s["name"]
+                    .as_str()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(name))
+                    && s["type"].as_str() == Some(typ.as_str())
+            })
+            .cloned()
+            .collect())
+    }
+
+    fn cached_record_id(&self) -> Option<String> {
+        self.record_id.read().expect("record_id lock").clone()
+    }
+
+    fn set_record_id(&self, id: Option<String>) {
+        *self.record_id.write().expect("record_id lock") = id;
+    }
+
+    async fn ensure_record_id(&self) -> Result<Option<String>, ProviderError> {
+        if let Some(id) = self.cached_record_id() {
+            return Ok(Some(id));
+        }
+        self.lookup_record(&self.zone_name, &self.record_name, self.rtype)
+            .await?;
+        Ok(self.cached_record_id())
+    }
+
+    /// Fetch the record set by name/type; caches its id when it is the
+    /// configured one.
+    async fn lookup_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<Value>, ProviderError> {
+        let zid = self.zone_id(zone).await?;
+        let set = self
+            .find(&zid, &absolute(name, zone), typ)
+            .await?
+            .into_iter()
+            .next();
+        if self.is_configured(zone, name, typ) {
+            self.set_record_id(
+                set.as_ref()
+                    .and_then(|s| s["id"].as_str())
+                    .map(str::to_owned),
+            );
+        }
+        Ok(set)
+    }
+
+    fn rrset_body(&self, ip: &str, ttl: u32) -> Value {
+        json!({
+            "name":    absolute(&self.record_name, &self.zone_name),
+            "type":    self.rtype.as_str(),
+            "ttl":     if ttl == 0 { DEFAULT_TTL } else { ttl },
+            "records": [record_value(self.rtype, ip)],
+        })
+    }
+
+    /*──────── create / update helpers ────────*/
+
+    async fn create_record(&self, ip: &str, ttl: u32) -> Result<(), ProviderError> {
+        let zid = self.zone_id(&self.zone_name).await?;
+        let v = self
+            .call(
+                Method::POST,
+                &format!("/v2/zones/{zid}/recordsets"),
+                &[],
+                Some(self.rrset_body(ip, ttl)),
+            )
+            .await?;
+        let id = v["id"]
+            .as_str()
+            .ok_or_else(|| ProviderError::Api("create: missing id".into()))?;
+        self.set_record_id(Some(id.to_owned()));
+        info!("Huawei created recordset id={id}");
+        Ok(())
+    }
+
+    async fn update_record(&self, rid: &str, ip: &str, ttl: u32) -> Result<(), ProviderError> {
+        let zid = self.zone_id(&self.zone_name).await?;
+        self.call(
+            Method::PUT,
+            &format!("/v2/zones/{zid}/recordsets/{rid}"),
+            &[],
+            Some(self.rrset_body(ip, ttl)),
+        )
+        .await?;
+        info!("Huawei updated recordset id={rid}");
+        Ok(())
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct HuaweiCfg {
+    access_key: String,
+    secret_key: String,
+    #[serde(default = "default_region")]
+    region: String,
+    endpoint: Option<String>,
+    project_id: Option<String>,
+}
+
+fn default_region() -> String {
+    "cn-north-4".into()
+}
+
+/// Builds `kind = "huawei"` entries; expects `access_key`, `secret_key` and
+/// optionally `region`, `endpoint` and `project_id`.
+pub struct HuaweiFactory;
+
+impl ProviderFactory for HuaweiFactory {
+    fn kind(&self) -> &'static str {
+        "huawei"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: HuaweiCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(HuaweiProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            &c.access_key,
+            &c.secret_key,
+            &c.region,
+            c.endpoint.as_deref(),
+            c.project_id.as_deref(),
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for HuaweiProvider {
+    fn name(&self) -> &'static str {
+        "Huawei"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    fn cache(&self) -> ProviderCache {
+        let mut c = ProviderCache::new();
+        if let Some(id) = self.zone_id.get() {
+            c.insert("zone_id".into(), id.clone());
+        }
+        if let Some(id) = self.cached_record_id() {
+            c.insert("record_id".into(), id);
+        }
+        c
+    }
+
+    fn restore_cache(&self, cache: &ProviderCache) {
+        if let Some(id) = cache.get("zone_id") {
+            let _ = self.zone_id.set(id.clone());
+        }
+        if let Some(id) = cache.get("record_id") {
+            self.set_record_id(Some(id.clone()));
+        }
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        Ok(self
+            .lookup_record(zone, name, typ)
+            .await?
+            .and_then(|s| s["records"][0].as_str().map(str::to_owned)))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        let zid = self.zone_id(zone).await?;
+        let apex = absolute("@", zone);
+        let suffix = format!(".{apex}");
+        let mut out = Vec::new();
+        let mut offset = 0;
+        loop {
+            let v = self
+                .call(
+                    Method::GET,
+                    &format!("/v2/zones/{zid}/recordsets"),
+                    &[
+                        ("limit", PAGE_SIZE.to_string()),
+                        ("offset", offset.to_string()),
+                    ],
+                    None,
+                )
+                .await?;
+            let sets = v["recordsets"].as_array().cloned().unwrap_or_default();
+            for s in &sets {
+                let (Some(full), Some(Ok(typ))) = (
+                    s["name"].as_str(),
+                    s["type"].as_str().map(str::parse::<RecordType>),
+                ) else {
+                    continue;
+                };
+                let name = match full.strip_suffix(&suffix) {
+                    Some(n) => n,
+                    None if full.eq_ignore_ascii_case(&apex) => "@",
+                    None => full,
+                };
+                for r in s["records"].as_array().into_iter().flatten() {
+                    out.push(DnsRecord {
+                        id: s["id"].as_str().unwrap_or_default().to_owned(),
+                        name: name.to_owned(),
+                        typ,
+                        value: r.as_str().unwrap_or_default().to_owned(),
+                        ttl: s["ttl"].as_u64().unwrap_or_default() as u32,
+                    });
+                }
+            }
+            offset += sets.len() as u64;
+            let total = v["metadata"]["total_count"].as_u64().unwrap_or_default();
+            if sets.is_empty() || offset >= total {
+                break;
+            }
+        }
+        Ok(out)
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        let zid = self.zone_id(zone).await?;
+        let name = absolute(name, zone);
+        let ids: Vec<String> = self
+            .find(&zid, &name, typ)
+            .await?
+            .iter()
+            .filter_map(|s| s["id"].as_str().map(str::to_owned))
+            .collect();
+        if ids.is_empty() {
+            return Err(ProviderError::NotFound(format!("{typ} {name}")));
+        }
+        for rid in ids {
+            self.call(
+                Method::DELETE,
+                &format!("/v2/zones/{zid}/recordsets/{rid}"),
+                &[],
+                None,
+            )
+            .await?;
+            info!("Huawei deleted recordset id={rid}");
+            if self.cached_record_id().as_deref() == Some(rid.as_str()) {
+                self.set_record_id(None);
+            }
+        }
+        Ok(())
+    }
+
+    async fn upsert_record(
+        &self,
+        _zone: &str,
+        _name: &str,
+        _typ: RecordType,
+        ip: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        match self.ensure_record_id().await? {
+            Some(rid) => match self.update_record(&rid, ip, ttl).await {
+                Err(ProviderError::NotFound(_)) => {
+                    self.set_record_id(None);
+                    self.create_record(ip, ttl).await
+                }
+                res => res,
+            },
+            None => self.create_record(ip, ttl).await,
+        }?;
+        debug!(
+            "Huawei upsert {}.{} -> {}",
+            self.record_name, self.zone_name, ip
+        );
+        Ok(())
+    }
+}
+
+/*──────── optional integration test (ignored) ────────*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn live_upsert() {
+        let ak = env::var("HUAWEI_AK").expect("HUAWEI_AK not set");
+        let sk = env::var("HUAWEI_SK").expect("HUAWEI_SK not set");
+        let p = HuaweiProvider::new(
+            "example.com",
+            "test-ddns",
+            "A",
+            &ak,
+            &sk,
+            "cn-north-4",
+            None,
+            None,
+        )
+        .unwrap();
+        p.upsert_record("example.com", "test-ddns", RecordType::A, "1.1.1.1", 300)
+            .await
+            .unwrap();
+        assert_eq!(
+            p.get_record("example.com", "test-ddns", RecordType::A)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("1.1.1.1")
+        );
+    }
+}
@@ -0,0 +1,430 @@
+//! Linode (Akamai Connected Cloud) DNS provider
+//!
+//! * Supports `A` / `AAAA` / `TXT` / `CNAME` record *upsert* (create or update).
+//! * Auth via **personal access token** with `Domains: Read/Write`.
+//! * Domain id and `record_id` are cached locally to reduce API calls.
+//! * Errors are mapped to [`ddns_provider::ProviderError`] by HTTP status
+//!   (auth / not-found / rate-limit / invalid input).
+//! * A cached `record_id` that vanished upstream is dropped and the record re-created.
+
+use async_trait::async_trait;
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderCache, ProviderError, ProviderFactory, ProviderSpec,
+    ProviderTable, RecordType, parse_table,
+};
+use once_cell::sync::OnceCell;
+use reqwest::{
+    Client, Response,
+    header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue, USER_AGENT},
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::{Arc, RwLock};
+use tracing::{debug, info};
+
+const API_ROOT: &str = "https://api.linode.com/v4";
+
+/// Linode names the apex `""`, ddns-rs `@`.
+fn api_name(name: &str) -> &str {
+    if name == "@" { "" } else { name }
+}
+
+/*──────── provider struct ────────*/
+
+pub struct LinodeProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    client: Client,
+
+    zone_id: OnceCell<u64>,
+    record_id: RwLock<Option<u64>>,
+}
+
+impl LinodeProvider {
+    pub fn new(zone: &str, record: &str, rtype: &str, token: &str) -> anyhow::Result<Self> {
+        let rtype: RecordType = rtype.parse()?;
+        if matches!(rtype, RecordType::HTTPS | RecordType::SVCB) {
+            anyhow::bail!("linode: {rtype} records are not supported");
+        }
+        let mut hdr = HeaderMap::new();
+        hdr.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {token}"))?,
+        );
+        hdr.insert(USER_AGENT, HeaderValue::from_static("ddns-rs (+github)"));
+        hdr.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        Ok(Self {
+            zone_name: zone.to_owned(),
+            record_name: record.to_owned(),
+            rtype,
+            client: Client::builder().default_headers(hdr).build()?,
+            zone_id: OnceCell::new(),
+            record_id: RwLock::new(None),
+        })
+    }
+
+    /*──────── tiny HTTP wrapper ────────*/
+
+    async fn get(&self, path: &str) -> Result<Value, ProviderError> {
+        self.check(self.client.get(format!("{API_ROOT}{path}")).send().await?)
+            .await
+    }
+    async fn post(&self, path: &str, body: Value) -> Result<Value, ProviderError> {
+        self.check(
+            self.client
+                .post(format!("{API_ROOT}{path}"))
+                .json(&body)
+                .send()
+                .await?,
+        )
+        .await
+    }
+    async fn put(&self, path: &str, body: Value) -> Result<Value, ProviderError> {
+        self.check(
+            self.client
+                .put(format!("{API_ROOT}{path}"))
+                .json(&body)
+                .send()
+                .await?,
+        )
+        .await
+    }
+
+    async fn delete(&self, path: &str) -> Result<Value, ProviderError> {
+        self.check(
+            self.client
+                .delete(format!("{API_ROOT}{path}"))
+                .send()
+                .await?,
+        )
+        .await
+    }
+
+    async fn check(&self, resp: Response) -> Result<Value, ProviderError> {
+        let status = resp.status();
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        let v: Value = resp.json().await.unwrap_or(Value::Null);
+        if status.is_success() {
+            return Ok(v);
+        }
+        // `{"errors":[{"reason":…,"field":…}]}`
+        let msg = v["errors"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|e| {
+                let reason = e["reason"].as_str()?;
+                Some(match e["field"].as_str() {
+                    Some(f) => format!("{f}: {reason}"),
+                    None => reason.to_owned(),
+                })
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        let msg = if msg.is_empty() {
+            format!("HTTP {status}")
+        } else {
+            msg
+        };
+        Err(ProviderError::from_status(status, msg, retry_after))
+    }
+
+    /// Every item of a paginated collection.
+    async fn collect(&self, path: &str) -> Result<Vec<Value>, ProviderError> {
+        let mut out = Vec::new();
+        let mut page = 1;
+        loop {
+            let v = self
+                .get(&format!("{path}?page={page}&page_size=500"))
+                .await?;
+            out.extend(v["data"].as_array().into_iter().flatten().cloned());
+            if page >= v["pages"].as_u64().unwrap_or(1) {
+                break;
+            }
+            page += 1;
+        }
+        Ok(out)
+    }
+
+    /*──────── zone / record helpers ────────*/
+
+    async fn ensure_zone_id(&self) -> Result<u64, ProviderError> {
+        if let Some(id) = self.zone_id.get() {
+            return Ok(*id);
+        }
+        let id = self.find_zone(&self.zone_name).await?;
+        let _ = self.zone_id.set(id);
+        Ok(id)
+    }
+
+    /// Domain id for `zone`; the configured zone goes through the cache.
+    async fn zone_id_for(&self, zone: &str) -> Result<u64, ProviderError> {
+        if zone == self.zone_name {
+            return self.ensure_zone_id().await;
+        }
+        self.find_zone(zone).await
+    }
+
+    async fn find_zone(&self, zone: &str) -> Result<u64, ProviderError> {
+        self.collect("/domains")
+            .await?
+            .iter()
+            .find(|d| {
+                d["domain"]
+                    .as_str()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(zone))
+            })
+            .and_then(|d| d["id"].as_u64())
+            .ok_or_else(|| ProviderError::NotFound(format!("domain {zone}")))
+    }
+
+    fn cached_record_id(&self) -> Option<u64> {
+        *self.record_id.read().expect("record_id lock")
+    }
+
+    fn set_record_id(&self, id: Option<u64>) {
+        *self.record_id.write().expect("record_id lock") = id;
+    }
+
+    async fn ensure_record_id(&self) -> Result<Option<u64>, ProviderError> {
+        if let Some(id) = self.cached_record_id() {
+            return Ok(Some(id));
+        }
+        self.lookup_record().await?;
+        Ok(self.cached_record_id())
+    }
+
+    /// Fetch the record by name/type; caches its id when found.
+    async fn lookup_record(&self) -> Result<Option<Value>, ProviderError> {
+        let zid = self.ensure_zone_id().await?;
+        let name = api_name(&self.record_name);
+        let rec = self
+            .collect(&format!("/domains/{zid}/records"))
+            .await?
+            .into_iter()
+            .find(|r| {
+                r["name"].as_str() == Some(name) && r["type"].as_str() == Some(self.rtype.as_str())
+            });
+        self.set_record_id(rec.as_ref().and_then(|r| r["id"].as_u64()));
+        Ok(rec)
+    }
+
+    /*──────── create / update helpers ────────*/
+
+    /// `ttl` 0 keeps the domain default; Linode rounds other values to
+    /// its nearest allowed TTL.
+    fn record_body(&self, content: &str, ttl: u32) -> Value {
+        json!({
+            "type":    self.rtype.as_str(),
+            "name":    api_name(&self.record_name),
+            "target":  content,
+            "ttl_sec": ttl,
+        })
+    }
+
+    async fn create_record(&self, zid: u64, content: &str, ttl: u32) -> Result<(), ProviderError> {
+        let v = self
+            .post(
+                &format!("/domains/{zid}/records"),
+                self.record_body(content, ttl),
+            )
+            .await?;
+        let id = v["id"]
+            .as_u64()
+            .ok_or_else(|| ProviderError::Api("create: missing id".into()))?;
+        self.set_record_id(Some(id));
+        info!("Linode created record id={id}");
+        Ok(())
+    }
+
+    async fn update_record(
+        &self,
+        zid: u64,
+        rid: u64,
+        content: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        self.put(
+            &format!("/domains/{zid}/records/{rid}"),
+            self.record_body(content, ttl),
+        )
+        .await?;
+        info!("Linode updated record id={rid}");
+        Ok(())
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct LinodeCfg {
+    token: String,
+}
+
+/// Builds `kind = "linode"` entries; expects `token`.
+pub struct LinodeFactory;
+
+impl ProviderFactory for LinodeFactory {
+    fn kind(&self) -> &'static str {
+        "linode"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: LinodeCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(LinodeProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            &c.token,
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for LinodeProvider {
+    fn name(&self) -> &'static str {
+        "Linode"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    fn cache(&self) -> ProviderCache {
+        let mut c = ProviderCache::new();
+        if let Some(id) = self.zone_id.get() {
+            c.insert("zone_id".into(), id.to_string());
+        }
+        if let Some(id) = self.cached_record_id() {
+            c.insert("record_id".into(), id.to_string());
+        }
+        c
+    }
+
+    fn restore_cache(&self, cache: &ProviderCache) {
+        if let Some(id) = cache.get("zone_id").and_then(|s| s.parse().ok()) {
+            let _ = self.zone_id.set(id);
+        }
+        if let Some(id) = cache.get("record_id").and_then(|s| s.parse().ok()) {
+            self.set_record_id(Some(id));
+        }
+    }
+
+    async fn get_record(
+        &self,
+        _zone: &str,
+        _name: &str,
+        _typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        Ok(self
+            .lookup_record()
+            .await?
+            .and_then(|r| r["target"].as_str().map(str::to_owned)))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        Ok(self
+            .collect(&format!("/domains/{zid}/records"))
+            .await?
+            .iter()
+            .filter_map(|r| {
+                let name = r["name"].as_str().unwrap_or_default();
+                Some(DnsRecord {
+                    id: r["id"].as_u64()?.to_string(),
+                    name: if name.is_empty() { "@" } else { name }.to_owned(),
+                    typ: r["type"].as_str()?.parse().ok()?,
+                    value: r["target"].as_str().unwrap_or_default().to_owned(),
+                    ttl: r["ttl_sec"].as_u64().unwrap_or_default() as u32,
+                })
+            })
+            .collect())
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        let ids: Vec<u64> = self
+            .collect(&format!("/domains/{zid}/records"))
+            .await?
+            .iter()
+            .filter(|r| {
+                r["name"].as_str() == Some(api_name(name))
+                    && r["type"].as_str() == Some(typ.as_str())
+            })
+            .filter_map(|r| r["id"].as_u64())
+            .collect();
+        if ids.is_empty() {
+            return Err(ProviderError::NotFound(format!("{typ} {name}.{zone}")));
+        }
+        for rid in ids {
+            self.delete(&format!("/domains/{zid}/records/{rid}"))
+                .await?;
+            info!("Linode deleted record id={rid}");
+            if self.cached_record_id() == Some(rid) {
+                self.set_record_id(None);
+            }
+        }
+        Ok(())
+    }
+
+    async fn upsert_record(
+        &self,
+        _zone: &str,
+        _name: &str,
+        _typ: RecordType,
+        ip: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        let zid = self.ensure_zone_id().await?;
+        match self.ensure_record_id().await? {
+            Some(rid) => match self.update_record(zid, rid, ip, ttl).await {
+                Err(ProviderError::NotFound(_)) => {
+                    self.set_record_id(None);
+                    self.create_record(zid, ip, ttl).await
+                }
+                res => res,
+            },
+            None => self.create_record(zid, ip, ttl).await,
+        }?;
+        debug!(
+            "Linode upsert {}.{} -> {}",
+            self.record_name, self.zone_name, ip
+        );
+        Ok(())
+    }
+}
+
+/*──────── optional integration test (ignored) ────────*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn live_upsert() {
+        let token = env::var("LINODE_TOKEN").expect("LINODE_TOKEN not set");
+        let p = LinodeProvider::new("example.com", "test-ddns", "A", &token).unwrap();
+        p.upsert_record("example.com", "test-ddns", RecordType::A, "1.1.1.1", 60)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,399 @@
+//! In-memory DNS provider – for tests and demos
+//!
+//! * Records live in a process-local table; nothing leaves the machine.
+//! * `delay` adds latency to every call, `fail_every` / `fail_with` inject
+//!   [`ddns_provider::ProviderError`]s on a fixed cadence.
+//! * Counters ([`MemoryProvider::calls`], [`MemoryProvider::writes`]) let tests
+//!   assert how often the scheduler really talked to the provider.
+
+use async_trait::async_trait;
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderError, ProviderFactory, ProviderSpec, ProviderTable,
+    RecordType, parse_table,
+};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+use tokio::time::sleep;
+use tracing::debug;
+
+/*──────── failure injection ────────*/
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailWith {
+    #[default]
+    Api,
+    Auth,
+    NotFound,
+    RateLimited,
+    InvalidInput,
+    Transient,
+}
+
+impl FailWith {
+    fn error(self, op: &str, retry_after: Duration) -> ProviderError {
+        let msg = format!("injected failure in {op}");
+        match self {
+            FailWith::Api => ProviderError::Api(msg),
+            FailWith::Auth => ProviderError::Auth(msg),
+            FailWith::NotFound => ProviderError::NotFound(msg),
+            FailWith::RateLimited => ProviderError::RateLimited {
+                retry_after: Some(retry_after),
+            },
+            FailWith::InvalidInput => ProviderError::InvalidInput(msg),
+            FailWith::Transient => ProviderError::Transient(msg),
+        }
+    }
+}
+
+/*──────── provider struct ────────*/
+
+/// Table key: (name relative to zone, record type).
+type Key = (String, &'static str);
+
+pub struct MemoryProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    ttl: u32,
+
+    records: Mutex<BTreeMap<Key, DnsRecord>>,
+    next_id: AtomicU64,
+    calls: AtomicU64,
+    writes: AtomicU64,
+
+    delay: Duration,
+    fail_every: u64,
+    fail_with: FailWith,
+    retry_after: Duration,
+}
+
+impl MemoryProvider {
+    pub fn new(zone: &str, record: &str, record_type: &str, ttl: u32) -> anyhow::Result<Self> {
+        Ok(Self {
+            zone_name: zone.to_owned(),
+            record_name: record.to_owned(),
+            rtype: record_type.parse()?,
+            ttl,
+            records: Mutex::new(BTreeMap::new()),
+            next_id: AtomicU64::new(1),
+            calls: AtomicU64::new(0),
+            writes: AtomicU64::new(0),
+            delay: Duration::ZERO,
+            fail_every: 0,
+            fail_with: FailWith::default(),
+            retry_after: Duration::from_secs(1),
+        })
+    }
+
+    /// Sleep for `delay` before every call.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Fail every `n`-th call (`0` = never) with `kind`.
+    pub fn with_failures(mut self, n: u64, kind: FailWith) -> Self {
+        self.fail_every = n;
+        self.fail_with = kind;
+        self
+    }
+
+    /// `Retry-After` carried by injected `rate_limited` failures (default 1s).
+    pub fn with_retry_after(mut self, d: Duration) -> Self {
+        self.retry_after = d;
+        self
+    }
+
+    /// Seed the configured record with `value`.
+    pub fn with_value(self, value: &str) -> Self {
+        self.put(&self.record_name.clone(), self.rtype, value, self.ttl);
+        self
+    }
+
+    /// Number of trait calls so far (including failed ones).
+    pub fn calls(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    /// Number of successful upserts so far.
+    pub fn writes(&self) -> u64 {
+        self.writes.load(Ordering::Relaxed)
+    }
+
+    /// Copy of the record table.
+    pub fn records(&self) -> Vec<DnsRecord> {
+        self.table().values().cloned().collect()
+    }
+
+    /*──────── helpers ────────*/
+
+    fn table(&self) -> std::sync::MutexGuard<'_, BTreeMap<Key, DnsRecord>> {
+        self.records.lock().expect("record table lock")
+    }
+
+    /// Count the call, apply the delay and the failure schedule.
+    async fn tick(&self, op: &str) -> Result<(), ProviderError> {
+        let n = self.calls.fetch_add(1, Ordering::Relaxed) + 1;
+        if !self.delay.is_zero() {
+            sleep(self.delay).await;
+        }
+        if self.fail_every > 0 && n.is_multiple_of(self.fail_every) {
+            return Err(self.fail_with.error(op, self.retry_after));
+        }
+        Ok(())
+    }
+
+    fn check_zone(&self, zone: &str) -> Result<(), ProviderError> {
+        if zone == self.zone_name {
+            Ok(())
+        } else {
+            Err(ProviderError::NotFound(format!("zone {zone}")))
+        }
+    }
+
+    fn put(&self, name: &str, typ: RecordType, value: &str, ttl: u32) {
+        let mut t = self.table();
+        let ent = t
+            .entry((name.to_owned(), typ.as_str()))
+            .or_insert_with(|| DnsRecord {
+                id: self.next_id.fetch_add(1, Ordering::Relaxed).to_string(),
+                name: name.to_owned(),
+                typ,
+                value: String::new(),
+                ttl,
+            });
+        ent.value = value.to_owned();
+        ent.ttl = ttl;
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct MemoryCfg {
+    #[serde(default)]
+    initial: Option<String>,
+    /// delay in milliseconds
+    #[serde(default)]
+    delay: u64,
+    #[serde(default)]
+    fail_every: u64,
+    #[serde(default)]
+    fail_with: FailWith,
+    /// `Retry-After` of injected `rate_limited` failures, in seconds
+    #[serde(default = "default_retry_after")]
+    retry_after: u64,
+}
+
+fn default_retry_after() -> u64 {
+    1
+}
+
+/// Builds `kind = "memory"` entries; optional `initial`, `delay` (ms),
+/// `fail_every`, `fail_with` and `retry_after` (s).
+pub struct MemoryFactory;
+
+impl ProviderFactory for MemoryFactory {
+    fn kind(&self) -> &'static str {
+        "memory"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: MemoryCfg = parse_table(self.kind(), table)?;
+        let mut p = MemoryProvider::new(spec.zone, spec.record, spec.record_type, spec.ttl)?
+            .with_delay(Duration::from_millis(c.delay))
+            .with_failures(c.fail_every, c.fail_with)
+            .with_retry_after(Duration::from_secs(c.retry_after));
+        if let Some(v) = &c.initial {
+            p = p.with_value(v);
+        }
+        Ok(Arc::new(p))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for MemoryProvider {
+    fn name(&self) -> &'static str {
+        "Memory"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        self.tick("get_record").await?;
+        self.check_zone(zone)?;
+        Ok(self
+            .table()
+            .get(&(name.to_owned(), typ.as_str()))
+            .map(|r| r.value.clone()))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        self.tick("list_records").await?;
+        self.check_zone(zone)?;
+        Ok(self.records())
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        self.tick("delete_record").await?;
+        self.check_zone(zone)?;
+        self.table()
+            .remove(&(name.to_owned(), typ.as_str()))
+            .map(|_| ())
+            .ok_or_else(|| ProviderError::NotFound(format!("{typ} {name}.{zone}")))
+    }
+
+    async fn upsert_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+        ip: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        self.tick("upsert_record").await?;
+        self.check_zone(zone)?;
+        self.put(name, typ, ip, ttl);
+        self.writes.fetch_add(1, Ordering::Relaxed);
+        debug!("Memory upsert {name}.{zone} -> {ip}");
+        Ok(())
+    }
+}
+
+/*──────── tests ────────*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider() -> MemoryProvider {
+        MemoryProvider::new("example.test", "home", "A", 300).unwrap()
+    }
+
+    #[tokio::test]
+    async fn seeded_value_and_counters() {
+        let p = provider().with_value("192.0.2.1");
+        let a = RecordType::A;
+        assert_eq!(
+            p.get_record("example.test", "home", a)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("192.0.2.1")
+        );
+        assert_eq!(
+            p.get_record("example.test", "home", RecordType::AAAA)
+                .await
+                .unwrap(),
+            None
+        );
+
+        p.upsert_record("example.test", "home", a, "192.0.2.2", 60)
+            .await
+            .unwrap();
+        p.upsert_record("example.test", "www", a, "192.0.2.3", 60)
+            .await
+            .unwrap();
+        let recs = p.records();
+        assert_eq!(recs.len(), 2);
+        // the seeded record keeps its id when overwritten
+        assert_eq!(
+            (recs[0].id.as_str(), recs[0].value.as_str(), recs[0].ttl),
+            ("1", "192.0.2.2", 60)
+        );
+        assert_eq!((p.calls(), p.writes()), (4, 2));
+
+        p.delete_record("example.test", "www", a).await.unwrap();
+        let err = p.delete_record("example.test", "www", a).await.unwrap_err();
+        assert!(matches!(err, ProviderError::NotFound(_)));
+        assert_eq!(p.list_records("example.test").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn other_zones_are_not_found() {
+        let p = provider().with_value("192.0.2.1");
+        let a = RecordType::A;
+        assert!(matches!(
+            p.get_record("other.test", "home", a).await,
+            Err(ProviderError::NotFound(_))
+        ));
+        assert!(matches!(
+            p.upsert_record("other.test", "home", a, "192.0.2.9", 60)
+                .await,
+            Err(ProviderError::NotFound(_))
+        ));
+        assert!(matches!(
+            p.list_records("other.test").await,
+            Err(ProviderError::NotFound(_))
+        ));
+        assert!(matches!(
+            p.delete_record("other.test", "home", a).await,
+            Err(ProviderError::NotFound(_))
+        ));
+        // rejected calls still count, but nothing was written
+        assert_eq!((p.calls(), p.writes()), (4, 0));
+        assert_eq!(p.records()[0].value, "192.0.2.1");
+    }
+
+    #[tokio::test]
+    async fn injected_failures() {
+        let p = provider()
+            .with_failures(3, FailWith::RateLimited)
+            .with_retry_after(Duration::from_secs(7));
+        let a = RecordType::A;
+        let mut failed = Vec::new();
+        for n in 1..=7 {
+            if let Err(e) = p
+                .upsert_record("example.test", "home", a, "192.0.2.1", 60)
+                .await
+            {
+                assert!(
+                    matches!(e, ProviderError::RateLimited { retry_after: Some(d) } if d == Duration::from_secs(7))
+                );
+                failed.push(n);
+            }
+        }
+        assert_eq!(failed, [3, 6]);
+        assert_eq!((p.calls(), p.writes()), (7, 5));
+
+        let p = provider().with_failures(1, FailWith::Auth);
+        let err = p.get_record("example.test", "home", a).await.unwrap_err();
+        assert!(matches!(err, ProviderError::Auth(m) if m == "injected failure in get_record"));
+
+        // `0` never fails
+        let p = provider().with_failures(0, FailWith::Api);
+        for _ in 0..5 {
+            p.get_record("example.test", "home", a).await.unwrap();
+        }
+    }
+}
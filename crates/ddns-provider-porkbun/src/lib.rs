@@ -0,0 +1,278 @@
+//! Porkbun DNS provider
+//!
+//! * Supports `A` / `AAAA` / `TXT` / `CNAME` / `HTTPS` / `SVCB` record *upsert*.
+//! * Auth via **API key + secret key**, sent in the JSON body of every
+//!   request (`apikey` / `secretapikey`); the domain must have *API Access*
+//!   enabled in the Porkbun dashboard.
+//! * Records are addressed by name and type (`…ByNameType` endpoints), so no
+//!   ids have to be cached.
+//! * Errors are `{"status":"ERROR","message":…}`; key problems are mapped to
+//!   [`ddns_provider::ProviderError::Auth`], the rest by HTTP status.
+
+use async_trait::async_trait;
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderError, ProviderFactory, ProviderSpec, ProviderTable,
+    RecordType, parse_table,
+};
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::{sync::Arc, time::Duration};
+use tracing::{debug, info};
+
+const API_ROOT: &str = "https://api.porkbun.com/api/json/v3";
+const MIN_TTL: u32 = 600; // Porkbun's minimum; also used for 0
+
+/// Map an `ERROR` reply to a [`ProviderError`].
+fn map_error(status: StatusCode, msg: &str, retry_after: Option<Duration>) -> ProviderError {
+    let lower = msg.to_ascii_lowercase();
+    if lower.contains("api key") || lower.contains("api access") {
+        ProviderError::Auth(msg.to_owned())
+    } else if lower.contains("rate limit") {
+        ProviderError::RateLimited { retry_after }
+    } else {
+        ProviderError::from_status(status, msg.to_owned(), retry_after)
+    }
+}
+
+/// Porkbun names the apex `""`, ddns-rs `@`.
+fn subdomain(name: &str) -> &str {
+    if name == "@" { "" } else { name }
+}
+
+/*──────── provider struct ────────*/
+
+pub struct PorkbunProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    api_key: String,
+    secret_key: String,
+    client: Client,
+}
+
+impl PorkbunProvider {
+    pub fn new(
+        zone: &str,
+        record: &str,
+        rtype: &str,
+        api_key: &str,
+        secret_key: &str,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            zone_name: zone.to_owned(),
+            record_name: record.to_owned(),
+            rtype: rtype.parse()?,
+            api_key: api_key.to_owned(),
+            secret_key: secret_key.to_owned(),
+            client: Client::new(),
+        })
+    }
+
+    /*──────── tiny HTTP wrapper ────────*/
+
+    /// POST `body` plus the keys to `path`; every endpoint is a POST.
+    async fn call(&self, path: &str, mut body: Value) -> Result<Value, ProviderError> {
+        body["apikey"] = json!(self.api_key);
+        body["secretapikey"] = json!(self.secret_key);
+        let resp = self
+            .client
+            .post(format!("{API_ROOT}{path}"))
+            .json(&body)
+            .send()
+            .await?;
+        let status = resp.status();
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        let v: Value = resp.json().await.unwrap_or(Value::Null);
+        if status.is_success() && v["status"] == "SUCCESS" {
+            return Ok(v);
+        }
+        let msg = v["message"]
+            .as_str()
+            .map(str::to_owned)
+            .unwrap_or_else(|| format!("HTTP {status}"));
+        Err(map_error(status, &msg, retry_after))
+    }
+
+    /// Records `name` / `typ` of `zone`.
+    async fn retrieve(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Vec<Value>, ProviderError> {
+        let v = self
+            .call(
+                &format!("/dns/retrieveByNameType/{zone}/{typ}/{}", subdomain(name)),
+                json!({}),
+            )
+            .await?;
+        Ok(v["records"].as_array().cloned().unwrap_or_default())
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct PorkbunCfg {
+    api_key: String,
+    secret_key: String,
+}
+
+/// Builds `kind = "porkbun"` entries; expects `api_key` and `secret_key`.
+pub struct PorkbunFactory;
+
+impl ProviderFactory for PorkbunFactory {
+    fn kind(&self) -> &'static str {
+        "porkbun"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: PorkbunCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(PorkbunProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            &c.api_key,
+            &c.secret_key,
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for PorkbunProvider {
+    fn name(&self) -> &'static str {
+        "Porkbun"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        Ok(self
+            .retrieve(zone, name, typ)
+            .await?
+            .first()
+            .and_then(|r| r["content"].as_str().map(str::to_owned)))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        let v = self
+            .call(&format!("/dns/retrieve/{zone}"), json!({}))
+            .await?;
+        let suffix = format!(".{zone}");
+        Ok(v["records"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|r| {
+                // names come back fully qualified
+                let full = r["name"].as_str()?;
+                let name = match full.strip_suffix(&suffix) {
+                    Some(n) => n,
+                    None if full == zone => "@",
+                    None => full,
+                };
+                Some(DnsRecord {
+                    id: match &r["id"] {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    },
+                    name: name.to_owned(),
+                    typ: r["type"].as_str()?.parse().ok()?,
+                    value: r["content"].as_str().unwrap_or_default().to_owned(),
+                    ttl: r["ttl"]
+                        .as_str()
+                        .and_then(|t| t.parse().ok())
+                        .unwrap_or_default(),
+                })
+            })
+            .collect())
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        if self.retrieve(zone, name, typ).await?.is_empty() {
+            return Err(ProviderError::NotFound(format!("{typ} {name}.{zone}")));
+        }
+        self.call(
+            &format!("/dns/deleteByNameType/{zone}/{typ}/{}", subdomain(name)),
+            json!({}),
+        )
+        .await?;
+        info!("Porkbun deleted {typ} {name}.{zone}");
+        Ok(())
+    }
+
+    async fn upsert_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+        ip: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        let ttl = ttl.max(MIN_TTL).to_string();
+        if self.retrieve(zone, name, typ).await?.is_empty() {
+            let v = self
+                .call(
+                    &format!("/dns/create/{zone}"),
+                    json!({
+                        "name":    subdomain(name),
+                        "type":    typ.as_str(),
+                        "content": ip,
+                        "ttl":     ttl,
+                    }),
+                )
+                .await?;
+            info!("Porkbun created record id={}", v["id"]);
+        } else {
+            self.call(
+                &format!("/dns/editByNameType/{zone}/{typ}/{}", subdomain(name)),
+                json!({ "content": ip, "ttl": ttl }),
+            )
+            .await?;
+        }
+        debug!("Porkbun upsert {name}.{zone} -> {ip}");
+        Ok(())
+    }
+}
+
+/*──────── optional integration test (ignored) ────────*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn live_upsert() {
+        let key = env::var("PORKBUN_API_KEY").expect("PORKBUN_API_KEY not set");
+        let secret = env::var("PORKBUN_SECRET_KEY").expect("PORKBUN_SECRET_KEY not set");
+        let p = PorkbunProvider::new("example.com", "test-ddns", "A", &key, &secret).unwrap();
+        p.upsert_record("example.com", "test-ddns", RecordType::A, "1.1.1.1", 600)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,307 @@
+//! PowerDNS Authoritative provider
+//!
+//! * Talks to the built-in HTTP API (`/api/v1/servers/<server_id>/zones/<zone>`)
+//!   with **`X-API-Key`** auth; base URL and server id are configurable.
+//! * Supports `A` / `AAAA` / `TXT` / `CNAME` / `HTTPS` / `SVCB`; a write is a
+//!   single `PATCH` replacing the rrset (`changetype: REPLACE`).
+//! * Names are sent canonical (absolute, trailing dot); TXT content is quoted
+//!   and CNAME targets made absolute as PowerDNS requires.
+//! * Errors are mapped to [`ddns_provider::ProviderError`] by HTTP status.
+
+use async_trait::async_trait;
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderError, ProviderFactory, ProviderSpec, ProviderTable,
+    RecordType,
+    name::{absolute, relative},
+    parse_table, record_value,
+};
+use reqwest::{
+    Client, Response,
+    header::{CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue, USER_AGENT},
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+const DEFAULT_URL: &str = "http://127.0.0.1:8081";
+const DEFAULT_SERVER_ID: &str = "localhost";
+const DEFAULT_TTL: u32 = 300;
+const API_KEY: HeaderName = HeaderName::from_static("x-api-key");
+
+/*──────── provider struct ────────*/
+
+pub struct PowerDnsProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    /// `<url>/api/v1/servers/<server_id>`
+    server_url: String,
+    client: Client,
+}
+
+impl PowerDnsProvider {
+    pub fn new(
+        zone: &str,
+        record: &str,
+        record_type: &str,
+        url: &str,
+        server_id: &str,
+        api_key: &str,
+    ) -> anyhow::Result<Self> {
+        let mut hdr = HeaderMap::new();
+        hdr.insert(API_KEY, HeaderValue::from_str(api_key)?);
+        hdr.insert(USER_AGENT, HeaderValue::from_static("ddns-rs (+github)"));
+        hdr.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        let base = url.trim_end_matches('/');
+        let base = base.strip_suffix("/api/v1").unwrap_or(base);
+        Ok(Self {
+            zone_name: zone.trim_end_matches('.').to_owned(),
+            record_name: record.to_owned(),
+            rtype: record_type.parse()?,
+            server_url: format!("{base}/api/v1/servers/{server_id}"),
+            client: Client::builder().default_headers(hdr).build()?,
+        })
+    }
+
+    /*──────── tiny HTTP wrapper ────────*/
+
+    fn zone_url(&self, zone: &str) -> String {
+        format!("{}/zones/{}", self.server_url, absolute("@", zone))
+    }
+
+    async fn check(&self, resp: Response) -> Result<Value, ProviderError> {
+        let status = resp.status();
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        // PATCH answers 204 without a body
+        let v: Value = resp.json().await.unwrap_or(Value::Null);
+        if status.is_success() {
+            return Ok(v);
+        }
+        let msg = v["error"]
+            .as_str()
+            .map(str::to_owned)
+            .unwrap_or_else(|| format!("HTTP {status}"));
+        Err(ProviderError::from_status(status, msg, retry_after))
+    }
+
+    /// Rrsets of `zone`, narrowed to `name` / `typ` when given (older
+    /// servers ignore the filter, so it is applied here as well).
+    async fn rrsets(
+        &self,
+        zone: &str,
+        filter: Option<(&str, RecordType)>,
+    ) -> Result<Vec<Value>, ProviderError> {
+        let mut req = self.client.get(self.zone_url(zone));
+        if let Some((name, typ)) = filter {
+            req = req.query(&[("rrset_name", name), ("rrset_type", typ.as_str())]);
+        }
+        let v = self.check(req.send().await?).await?;
+        Ok(v["rrsets"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|s| match filter {
+                Some((name, typ)) => {
+                    s["name"]
+                        .as_str()
+                        .is_some_and(|n| n.eq_ignore_ascii_case(name))
+                        && s["type"].as_str() == Some(typ.as_str())
+                }
+                None => true,
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn patch(&self, zone: &str, rrset: Value) -> Result<(), ProviderError> {
+        self.check(
+            self.client
+                .patch(self.zone_url(zone))
+                .json(&json!({ "rrsets": [rrset] }))
+                .send()
+                .await?,
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct PowerDnsCfg {
+    api_key: String,
+    #[serde(default = "default_url")]
+    url: String,
+    #[serde(default = "default_server_id")]
+    server_id: String,
+}
+
+fn default_url() -> String {
+    DEFAULT_URL.into()
+}
+
+fn default_server_id() -> String {
+    DEFAULT_SERVER_ID.into()
+}
+
+/// Builds `kind = "powerdns"` entries; expects `api_key` and optionally
+/// `url` and `server_id`.
+pub struct PowerDnsFactory;
+
+impl ProviderFactory for PowerDnsFactory {
+    fn kind(&self) -> &'static str {
+        "powerdns"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: PowerDnsCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(PowerDnsProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            &c.url,
+            &c.server_id,
+            &c.api_key,
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for PowerDnsProvider {
+    fn name(&self) -> &'static str {
+        "PowerDNS"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        let sets = self
+            .rrsets(zone, Some((&absolute(name, zone), typ)))
+            .await?;
+        Ok(sets
+            .iter()
+            .flat_map(|s| s["records"].as_array().into_iter().flatten())
+            .filter(|r| !r["disabled"].as_bool().unwrap_or(false))
+            .find_map(|r| r["content"].as_str().map(str::to_owned)))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        let mut out = Vec::new();
+        for s in self.rrsets(zone, None).await? {
+            let (Some(name), Some(Ok(typ))) = (
+                s["name"].as_str(),
+                s["type"].as_str().map(str::parse::<RecordType>),
+            ) else {
+                continue;
+            };
+            for r in s["records"].as_array().into_iter().flatten() {
+                out.push(DnsRecord {
+                    id: format!("{name}/{typ}"),
+                    name: relative(name, zone),
+                    typ,
+                    value: r["content"].as_str().unwrap_or_default().to_owned(),
+                    ttl: s["ttl"].as_u64().unwrap_or_default() as u32,
+                });
+            }
+        }
+        Ok(out)
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        let fqdn = absolute(name, zone);
+        // DELETE of a missing rrset succeeds silently; report it like the others
+        if self.rrsets(zone, Some((&fqdn, typ))).await?.is_empty() {
+            return Err(ProviderError::NotFound(format!("{typ} {fqdn}")));
+        }
+        self.patch(
+            zone,
+            json!({ "name": fqdn, "type": typ.as_str(), "changetype": "DELETE" }),
+        )
+        .await?;
+        info!("PowerDNS deleted {typ} {fqdn}");
+        Ok(())
+    }
+
+    async fn upsert_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+        ip: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        let fqdn = absolute(name, zone);
+        self.patch(
+            zone,
+            json!({
+                "name":       fqdn,
+                "type":       typ.as_str(),
+                "ttl":        if ttl == 0 { DEFAULT_TTL } else { ttl },
+                "changetype": "REPLACE",
+                "records":    [{ "content": record_value(typ, ip), "disabled": false }],
+            }),
+        )
+        .await?;
+        debug!("PowerDNS upsert {fqdn} -> {ip}");
+        Ok(())
+    }
+}
+
+/*──────── optional integration test (ignored) ────────*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /// Against a local `pdns_server` with the API enabled.
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn live_upsert() {
+        let key = env::var("PDNS_API_KEY").expect("PDNS_API_KEY not set");
+        let p = PowerDnsProvider::new(
+            "example.com",
+            "test-ddns",
+            "A",
+            DEFAULT_URL,
+            DEFAULT_SERVER_ID,
+            &key,
+        )
+        .unwrap();
+        p.upsert_record("example.com", "test-ddns", RecordType::A, "1.1.1.1", 60)
+            .await
+            .unwrap();
+        assert_eq!(
+            p.get_record("example.com", "test-ddns", RecordType::A)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("1.1.1.1")
+        );
+    }
+}
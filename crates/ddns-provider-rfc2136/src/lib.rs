@@ -0,0 +1,468 @@
+//! RFC 2136 dynamic-update DNS provider
+//!
+//! * Sends DNS UPDATE messages straight to an authoritative server (BIND, Knot, PowerDNS, …).
+//! * Every request is TSIG-signed (`hmac-sha256` / `hmac-sha512`); replies must be signed too.
+//! * UDP first, TCP when the reply is truncated.
+//! * `list_records` would need AXFR and is not supported.
+
+mod wire;
+
+use async_trait::async_trait;
+use base64::{Engine, engine::general_purpose::STANDARD as B64};
+use ddns_provider::{
+    DnsProvider, ProviderError, ProviderFactory, ProviderSpec, ProviderTable, RecordType,
+    name::fqdn, parse_table,
+};
+use serde::Deserialize;
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpStream, UdpSocket, lookup_host},
+    time::timeout,
+};
+use tracing::{debug, info};
+use wire::{
+    Algorithm, Builder, CLASS_ANY, CLASS_IN, Message, OP_QUERY, OP_UPDATE, RCODE_NOERROR,
+    RCODE_NXDOMAIN, RCODE_NXRRSET, TYPE_SOA, TsigKey,
+};
+
+const DEFAULT_PORT: u16 = 53;
+const DEFAULT_TIMEOUT_MS: u64 = 5_000;
+const DEFAULT_TTL: u32 = 300;
+
+/*──────── provider struct ────────*/
+
+pub struct Rfc2136Provider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    /// `host:port`, resolved on every exchange
+    server: String,
+    key: TsigKey,
+    timeout: Duration,
+}
+
+impl Rfc2136Provider {
+    /// `secret` is the base64 key material as found in `tsig-keygen` output.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        zone: &str,
+        record: &str,
+        record_type: &str,
+        server: &str,
+        key_name: &str,
+        secret: &str,
+        algorithm: &str,
+        timeout: Duration,
+    ) -> anyhow::Result<Self> {
+        let secret = B64
+            .decode(secret.trim())
+            .map_err(|e| anyhow::anyhow!("TSIG secret is not valid base64: {e}"))?;
+        Ok(Self {
+            zone_name: zone.trim_end_matches('.').to_owned(),
+            record_name: record.to_owned(),
+            rtype: record_type.parse()?,
+            server: with_port(server),
+            key: TsigKey::new(key_name, algorithm.parse()?, secret)?,
+            timeout,
+        })
+    }
+
+    /*──────── transport ────────*/
+
+    /// Sign `msg`, send it and return the verified reply.
+    async fn exchange(&self, mut msg: Vec<u8>) -> Result<(Vec<u8>, Message), ProviderError> {
+        let id = u16::from_be_bytes([msg[0], msg[1]]);
+        let mac = self.key.sign(&mut msg, None, unix_now());
+        let addr = lookup_host(&self.server)
+            .await
+            .map_err(|e| ProviderError::Transient(format!("resolve `{}`: {e}", self.server)))?
+            .next()
+            .ok_or_else(|| {
+                ProviderError::InvalidInput(format!("`{}` has no address", self.server))
+            })?;
+
+        let fut = async {
+            let raw = udp(addr, &msg).await?;
+            if wire::is_truncated(&raw) {
+                debug!("RFC2136 reply from {addr} truncated; retrying over TCP");
+                return tcp(addr, &msg).await;
+            }
+            Ok(raw)
+        };
+        let raw = timeout(self.timeout, fut)
+            .await
+            .map_err(|_| {
+                ProviderError::Transient(format!(
+                    "{addr} did not answer within {}ms",
+                    self.timeout.as_millis()
+                ))
+            })?
+            .map_err(|e| ProviderError::Transient(format!("{addr}: {e}")))?;
+
+        let reply = wire::parse(&raw)?;
+        if reply.id != id {
+            return Err(ProviderError::Api("reply id does not match request".into()));
+        }
+        match &reply.tsig {
+            Some(t) if !t.mac.is_empty() => self
+                .key
+                .verify(&raw, t, Some(&mac), unix_now())
+                .map_err(|e| {
+                    ProviderError::Auth(format!("reply TSIG invalid: {}", wire::tsig_error_name(e)))
+                })?,
+            Some(t) if t.error != 0 => {
+                return Err(ProviderError::Auth(format!(
+                    "server rejected TSIG: {}",
+                    wire::tsig_error_name(t.error)
+                )));
+            }
+            _ if reply.rcode() == RCODE_NOERROR => {
+                return Err(ProviderError::Auth("reply is not TSIG-signed".into()));
+            }
+            _ => {}
+        }
+        Ok((raw, reply))
+    }
+}
+
+/// Append the default DNS port unless `server` already has one.
+fn with_port(server: &str) -> String {
+    match server.parse::<IpAddr>() {
+        Ok(ip) => SocketAddr::new(ip, DEFAULT_PORT).to_string(),
+        Err(_) if server.contains(':') => server.to_owned(),
+        Err(_) => format!("{server}:{DEFAULT_PORT}"),
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn new_id() -> u16 {
+    getrandom::u32().unwrap_or_else(|_| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or_default()
+    }) as u16
+}
+
+async fn udp(addr: SocketAddr, msg: &[u8]) -> io::Result<Vec<u8>> {
+    let local: SocketAddr = if addr.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let sock = UdpSocket::bind(local).await?;
+    sock.connect(addr).await?;
+    sock.send(msg).await?;
+    let mut buf = vec![0; 65_535];
+    loop {
+        let n = sock.recv(&mut buf).await?;
+        // ignore stray datagrams carrying a different id
+        if n >= 12 && buf[..2] == msg[..2] {
+            buf.truncate(n);
+            return Ok(buf);
+        }
+    }
+}
+
+async fn tcp(addr: SocketAddr, msg: &[u8]) -> io::Result<Vec<u8>> {
+    let mut stream = TcpStream::connect(addr).await?;
+    let mut framed = Vec::with_capacity(msg.len() + 2);
+    framed.extend_from_slice(&(msg.len() as u16).to_be_bytes());
+    framed.extend_from_slice(msg);
+    stream.write_all(&framed).await?;
+    let len = stream.read_u16().await? as usize;
+    let mut buf = vec![0; len];
+    stream.read_exact(&mut buf).await?;
+    Ok(buf)
+}
+
+/// Map a non-zero RCODE to a [`ProviderError`].
+fn check(op: &str, reply: &Message) -> Result<(), ProviderError> {
+    let rcode = reply.rcode();
+    let msg = |name: &str| format!("{op}: server answered {name}");
+    match rcode {
+        0 => Ok(()),
+        1 => Err(ProviderError::InvalidInput(msg("FORMERR"))),
+        2 => Err(ProviderError::Transient(msg("SERVFAIL"))),
+        3 => Err(ProviderError::NotFound(msg("NXDOMAIN"))),
+        4 => Err(ProviderError::Api(msg("NOTIMP"))),
+        5 => Err(ProviderError::Auth(msg("REFUSED"))),
+        6 => Err(ProviderError::InvalidInput(msg("YXDOMAIN"))),
+        7 => Err(ProviderError::InvalidInput(msg("YXRRSET"))),
+        8 => Err(ProviderError::InvalidInput(msg("NXRRSET"))),
+        9 => Err(ProviderError::Auth(msg("NOTAUTH"))),
+        10 => Err(ProviderError::NotFound(msg("NOTZONE"))),
+        n => Err(ProviderError::Api(msg(&format!("RCODE {n}")))),
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct Rfc2136Cfg {
+    /// `host`, `host:port`, `ip` or `[ipv6]:port`
+    server: String,
+    key_name: String,
+    secret: String,
+    #[serde(default = "default_algorithm")]
+    algorithm: String,
+    /// timeout in milliseconds
+    #[serde(default)]
+    timeout: Option<u64>,
+}
+
+fn default_algorithm() -> String {
+    Algorithm::HmacSha256.name().into()
+}
+
+/// Builds `kind = "rfc2136"` entries; expects `server`, `key_name` and
+/// `secret`, optionally `algorithm` and `timeout` (ms).
+pub struct Rfc2136Factory;
+
+impl ProviderFactory for Rfc2136Factory {
+    fn kind(&self) -> &'static str {
+        "rfc2136"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: Rfc2136Cfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(Rfc2136Provider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            &c.server,
+            &c.key_name,
+            &c.secret,
+            &c.algorithm,
+            Duration::from_millis(c.timeout.unwrap_or(DEFAULT_TIMEOUT_MS)),
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for Rfc2136Provider {
+    fn name(&self) -> &'static str {
+        "RFC2136"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        let fqdn = fqdn(name, zone);
+        let code = wire::type_code(typ);
+        let mut b = Builder::new(new_id(), OP_QUERY);
+        b.question(&fqdn, code, CLASS_IN)?;
+        let (raw, reply) = self.exchange(b.finish()).await?;
+        if reply.rcode() == RCODE_NXDOMAIN {
+            return Ok(None);
+        }
+        check("query", &reply)?;
+        Ok(reply.sections[0]
+            .iter()
+            .find(|rr| {
+                rr.typ == code && rr.class == CLASS_IN && rr.name.eq_ignore_ascii_case(&fqdn)
+            })
+            .and_then(|rr| wire::decode_rdata(&raw, rr)))
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        let fqdn = fqdn(name, zone);
+        let code = wire::type_code(typ);
+        // prerequisite "RRset exists": a missing set fails with NXRRSET
+        // instead of the delete silently changing nothing
+        let mut b = Builder::new(new_id(), OP_UPDATE);
+        b.question(zone, TYPE_SOA, CLASS_IN)?
+            .record(1, &fqdn, code, CLASS_ANY, 0, &[])?
+            .record(2, &fqdn, code, CLASS_ANY, 0, &[])?;
+        let (_, reply) = self.exchange(b.finish()).await?;
+        if reply.rcode() == RCODE_NXRRSET {
+            return Err(ProviderError::NotFound(format!("{typ} {fqdn}")));
+        }
+        check("update", &reply)?;
+        info!("RFC2136 deleted {typ} {fqdn}");
+        Ok(())
+    }
+
+    async fn upsert_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+        ip: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        let fqdn = fqdn(name, zone);
+        let code = wire::type_code(typ);
+        let rdata = wire::encode_rdata(typ, ip)?;
+        let ttl = if ttl == 0 { DEFAULT_TTL } else { ttl };
+
+        // replace the whole RRset: delete it, then add the new record
+        let mut b = Builder::new(new_id(), OP_UPDATE);
+        b.question(zone, TYPE_SOA, CLASS_IN)?
+            .record(2, &fqdn, code, CLASS_ANY, 0, &[])?
+            .record(2, &fqdn, code, CLASS_IN, ttl, &rdata)?;
+        let (_, reply) = self.exchange(b.finish()).await?;
+        check("update", &reply)?;
+        debug!("RFC2136 upsert {fqdn} -> {ip}");
+        Ok(())
+    }
+}
+
+/*──────── local tests (in-process DNS server) ────────*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    const SECRET: &str = "c2VjcmV0LXNlY3JldC1zZWNyZXQtc2VjcmV0LXNlY3JldA==";
+    const OTHER: &str = "b3RoZXItb3RoZXItb3RoZXItb3RoZXItb3RoZXItb3RoZXI=";
+
+    type Zone = Arc<Mutex<HashMap<(String, u16), Vec<Vec<u8>>>>>;
+
+    /// Tiny authoritative server: verifies TSIG, applies updates, answers
+    /// queries and signs its replies.
+    async fn serve(alg: Algorithm) -> (String, Zone) {
+        let key = TsigKey::new("ddns-key.", alg, B64.decode(SECRET).unwrap()).unwrap();
+        let sock = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = sock.local_addr().unwrap().to_string();
+        let zone = Zone::default();
+        let records = zone.clone();
+        tokio::spawn(async move {
+            let mut buf = vec![0; 4096];
+            loop {
+                let (n, peer) = sock.recv_from(&mut buf).await.unwrap();
+                let req = &buf[..n];
+                let msg = wire::parse(req).unwrap();
+                let tsig = msg.tsig.as_ref().unwrap();
+                let flags = 0x8000 | (msg.flags & 0x7800);
+
+                let reply = match key.verify(req, tsig, None, unix_now()) {
+                    Err(code) => {
+                        let mut out = Builder::new(msg.id, flags | 9).finish();
+                        key.append(&mut out, tsig.time, &[], code);
+                        out
+                    }
+                    Ok(()) => {
+                        let (qname, end) = wire::read_name(req, 12).unwrap();
+                        let qtype = u16::from_be_bytes([req[end], req[end + 1]]);
+                        let mut z = records.lock().unwrap();
+                        // "RRset exists" prerequisites
+                        let missing = msg.sections[0]
+                            .iter()
+                            .any(|rr| !z.contains_key(&(rr.name.clone(), rr.typ)));
+                        let rcode = if missing { RCODE_NXRRSET as u16 } else { 0 };
+                        let mut b = Builder::new(msg.id, flags | rcode);
+                        b.question(&qname, qtype, CLASS_IN).unwrap();
+                        if msg.flags & 0x7800 == OP_QUERY {
+                            for rd in z.get(&(qname.clone(), qtype)).into_iter().flatten() {
+                                b.record(1, &qname, qtype, CLASS_IN, 60, rd).unwrap();
+                            }
+                        } else if !missing {
+                            for rr in &msg.sections[1] {
+                                let k = (rr.name.clone(), rr.typ);
+                                if rr.class == CLASS_ANY {
+                                    z.remove(&k);
+                                } else {
+                                    z.entry(k).or_default().push(req[rr.rdata.clone()].to_vec());
+                                }
+                            }
+                        }
+                        let mut out = b.finish();
+                        key.sign(&mut out, Some(&tsig.mac), unix_now());
+                        out
+                    }
+                };
+                sock.send_to(&reply, peer).await.unwrap();
+            }
+        });
+        (addr, zone)
+    }
+
+    fn provider(server: &str, secret: &str, alg: &str) -> Rfc2136Provider {
+        Rfc2136Provider::new(
+            "example.test",
+            "home",
+            "A",
+            server,
+            "ddns-key",
+            secret,
+            alg,
+            Duration::from_secs(2),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn update_and_read_back() {
+        let (server, zone) = serve(Algorithm::HmacSha256).await;
+        let p = provider(&server, SECRET, "hmac-sha256");
+        let get = || p.get_record("example.test", "home", RecordType::A);
+        assert_eq!(get().await.unwrap(), None);
+
+        for ip in ["192.0.2.10", "192.0.2.11"] {
+            p.upsert_record("example.test", "home", RecordType::A, ip, 60)
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            zone.lock().unwrap()[&("home.example.test".into(), 1)].len(),
+            1
+        );
+        assert_eq!(get().await.unwrap().as_deref(), Some("192.0.2.11"));
+
+        p.delete_record("example.test", "home", RecordType::A)
+            .await
+            .unwrap();
+        assert_eq!(get().await.unwrap(), None);
+        let err = p.delete_record("example.test", "home", RecordType::A).await;
+        assert!(matches!(err, Err(ProviderError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn wrong_secret_is_auth_error() {
+        let (server, _) = serve(Algorithm::HmacSha512).await;
+        let p = provider(&server, OTHER, "hmac-sha512");
+        let err = p
+            .upsert_record("example.test", "home", RecordType::A, "192.0.2.10", 60)
+            .await;
+        assert!(matches!(err, Err(ProviderError::Auth(m)) if m.contains("BADSIG")));
+    }
+}
@@ -0,0 +1,517 @@
+//! Minimal DNS wire format for dynamic updates
+//!
+//! * Builds QUERY / UPDATE messages (names are never compressed on output).
+//! * Parses replies, following compression pointers.
+//! * Signs and verifies TSIG records (RFC 8945).
+
+use ddns_provider::{ProviderError, RecordType};
+use hmac::{Hmac, Mac, digest::KeyInit};
+use sha2::{Sha256, Sha512};
+use std::{
+    net::{Ipv4Addr, Ipv6Addr},
+    ops::Range,
+    str::FromStr,
+};
+
+pub const CLASS_IN: u16 = 1;
+pub const CLASS_ANY: u16 = 255;
+pub const TYPE_SOA: u16 = 6;
+const TYPE_TSIG: u16 = 250;
+
+pub const OP_QUERY: u16 = 0;
+pub const OP_UPDATE: u16 = 5 << 11;
+const FLAG_TC: u16 = 0x0200;
+
+pub const RCODE_NOERROR: u8 = 0;
+pub const RCODE_NXDOMAIN: u8 = 3;
+pub const RCODE_NXRRSET: u8 = 8;
+
+const FUDGE: u16 = 300;
+
+fn malformed() -> ProviderError {
+    ProviderError::Api("malformed DNS message".into())
+}
+
+fn be16(msg: &[u8], p: usize) -> Result<u16, ProviderError> {
+    msg.get(p..p + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(malformed)
+}
+
+fn be32(msg: &[u8], p: usize) -> Result<u32, ProviderError> {
+    msg.get(p..p + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(malformed)
+}
+
+/*──────── record data ────────*/
+
+pub fn type_code(t: RecordType) -> u16 {
+    match t {
+        RecordType::A => 1,
+        RecordType::CNAME => 5,
+        RecordType::TXT => 16,
+        RecordType::AAAA => 28,
+        RecordType::SVCB => 64,
+        RecordType::HTTPS => 65,
+    }
+}
+
+/// Encode a presentation-format `value` as RDATA.
+pub fn encode_rdata(t: RecordType, value: &str) -> Result<Vec<u8>, ProviderError> {
+    let bad = || ProviderError::InvalidInput(format!("`{value}` is not a valid {t} value"));
+    match t {
+        RecordType::A => value
+            .parse::<Ipv4Addr>()
+            .map(|ip| ip.octets().to_vec())
+            .map_err(|_| bad()),
+        RecordType::AAAA => value
+            .parse::<Ipv6Addr>()
+            .map(|ip| ip.octets().to_vec())
+            .map_err(|_| bad()),
+        RecordType::TXT => {
+            let v = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            let mut out = Vec::new();
+            for chunk in v.as_bytes().chunks(255) {
+                out.push(chunk.len() as u8);
+                out.extend_from_slice(chunk);
+            }
+            if out.is_empty() {
+                out.push(0);
+            }
+            Ok(out)
+        }
+        RecordType::CNAME => {
+            let mut out = Vec::new();
+            push_name(&mut out, value)?;
+            Ok(out)
+        }
+        RecordType::HTTPS | RecordType::SVCB => Err(ProviderError::Unsupported(
+            "HTTPS / SVCB records over RFC 2136",
+        )),
+    }
+}
+
+/// Presentation form of `rr`; `None` for types we cannot decode.
+pub fn decode_rdata(msg: &[u8], rr: &Rr) -> Option<String> {
+    let data = msg.get(rr.rdata.clone())?;
+    match rr.typ {
+        1 => Some(Ipv4Addr::from(<[u8; 4]>::try_from(data).ok()?).to_string()),
+        28 => Some(Ipv6Addr::from(<[u8; 16]>::try_from(data).ok()?).to_string()),
+        16 => {
+            let mut out = String::new();
+            let mut rest = data;
+            while let Some((&n, tail)) = rest.split_first() {
+                let s = tail.get(..n as usize)?;
+                out.push_str(&String::from_utf8_lossy(s));
+                rest = &tail[n as usize..];
+            }
+            Some(out)
+        }
+        5 => read_name(msg, rr.rdata.start).ok().map(|(n, _)| n),
+        _ => None,
+    }
+}
+
+/*──────── names ────────*/
+
+/// Append `name` in uncompressed wire form (a trailing dot is optional).
+pub fn push_name(buf: &mut Vec<u8>, name: &str) -> Result<(), ProviderError> {
+    let bad = || ProviderError::InvalidInput(format!("invalid DNS name `{name}`"));
+    let trimmed = name.strip_suffix('.').unwrap_or(name);
+    let start = buf.len();
+    if !trimmed.is_empty() {
+        for label in trimmed.split('.') {
+            if label.is_empty() || label.len() > 63 {
+                return Err(bad());
+            }
+            buf.push(label.len() as u8);
+            buf.extend_from_slice(label.as_bytes());
+        }
+    }
+    buf.push(0);
+    if buf.len() - start > 255 {
+        return Err(bad());
+    }
+    Ok(())
+}
+
+/// Read the name at `pos`; returns it without trailing dot and the offset
+/// right after it.
+pub fn read_name(msg: &[u8], mut pos: usize) -> Result<(String, usize), ProviderError> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    // bounded so that pointer loops cannot hang us
+    for _ in 0..128 {
+        let len = *msg.get(pos).ok_or_else(malformed)? as usize;
+        match len {
+            0 => return Ok((labels.join("."), end.unwrap_or(pos + 1))),
+            l if l & 0xC0 == 0xC0 => {
+                let lo = *msg.get(pos + 1).ok_or_else(malformed)? as usize;
+                end.get_or_insert(pos + 2);
+                pos = ((l & 0x3F) << 8) | lo;
+            }
+            l if l < 64 => {
+                let label = msg.get(pos + 1..pos + 1 + l).ok_or_else(malformed)?;
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                pos += 1 + l;
+            }
+            _ => return Err(malformed()),
+        }
+    }
+    Err(malformed())
+}
+
+/*──────── building ────────*/
+
+/// Message builder; sections must be filled in wire order
+/// (question / zone → answer / prerequisite → authority / update → additional).
+pub struct Builder {
+    buf: Vec<u8>,
+    counts: [u16; 4],
+}
+
+impl Builder {
+    pub fn new(id: u16, flags: u16) -> Self {
+        let mut buf = Vec::with_capacity(512);
+        buf.extend_from_slice(&id.to_be_bytes());
+        buf.extend_from_slice(&flags.to_be_bytes());
+        buf.extend_from_slice(&[0; 8]);
+        Self {
+            buf,
+            counts: [0; 4],
+        }
+    }
+
+    /// Question (QUERY) or zone (UPDATE) entry.
+    pub fn question(
+        &mut self,
+        name: &str,
+        typ: u16,
+        class: u16,
+    ) -> Result<&mut Self, ProviderError> {
+        push_name(&mut self.buf, name)?;
+        self.buf.extend_from_slice(&typ.to_be_bytes());
+        self.buf.extend_from_slice(&class.to_be_bytes());
+        self.counts[0] += 1;
+        Ok(self)
+    }
+
+    /// Resource record in `section` (1 = answer, 2 = authority, 3 = additional).
+    pub fn record(
+        &mut self,
+        section: usize,
+        name: &str,
+        typ: u16,
+        class: u16,
+        ttl: u32,
+        rdata: &[u8],
+    ) -> Result<&mut Self, ProviderError> {
+        push_name(&mut self.buf, name)?;
+        self.buf.extend_from_slice(&typ.to_be_bytes());
+        self.buf.extend_from_slice(&class.to_be_bytes());
+        self.buf.extend_from_slice(&ttl.to_be_bytes());
+        self.buf
+            .extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        self.buf.extend_from_slice(rdata);
+        self.counts[section] += 1;
+        Ok(self)
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        for (i, c) in self.counts.iter().enumerate() {
+            self.buf[4 + 2 * i..6 + 2 * i].copy_from_slice(&c.to_be_bytes());
+        }
+        self.buf
+    }
+}
+
+/*──────── parsing ────────*/
+
+pub struct Rr {
+    pub name: String,
+    pub typ: u16,
+    pub class: u16,
+    /// RDATA position inside the message (names in it may be compressed)
+    pub rdata: Range<usize>,
+}
+
+pub struct Tsig {
+    /// offset of the TSIG record; the signed data ends here
+    start: usize,
+    key: String,
+    alg: String,
+    pub time: u64,
+    fudge: u16,
+    pub mac: Vec<u8>,
+    orig_id: u16,
+    pub error: u16,
+    other: Vec<u8>,
+}
+
+pub struct Message {
+    pub id: u16,
+    pub flags: u16,
+    /// answer / prerequisite, authority / update, additional (without TSIG)
+    pub sections: [Vec<Rr>; 3],
+    pub tsig: Option<Tsig>,
+}
+
+impl Message {
+    pub fn rcode(&self) -> u8 {
+        (self.flags & 0x000F) as u8
+    }
+}
+
+/// `true` when the TC bit is set and the query has to be repeated over TCP.
+pub fn is_truncated(msg: &[u8]) -> bool {
+    be16(msg, 2).is_ok_and(|f| f & FLAG_TC != 0)
+}
+
+pub fn parse(msg: &[u8]) -> Result<Message, ProviderError> {
+    let id = be16(msg, 0)?;
+    let flags = be16(msg, 2)?;
+    let counts = [be16(msg, 4)?, be16(msg, 6)?, be16(msg, 8)?, be16(msg, 10)?];
+    let mut pos = 12;
+
+    for _ in 0..counts[0] {
+        let (_, p) = read_name(msg, pos)?;
+        be16(msg, p + 2)?;
+        pos = p + 4;
+    }
+
+    let mut sections: [Vec<Rr>; 3] = Default::default();
+    let mut tsig = None;
+    for (s, section) in sections.iter_mut().enumerate() {
+        let count = counts[s + 1];
+        for i in 0..count {
+            let start = pos;
+            let (name, p) = read_name(msg, pos)?;
+            let typ = be16(msg, p)?;
+            let class = be16(msg, p + 2)?;
+            let len = be16(msg, p + 8)? as usize;
+            let rdata = p + 10..p + 10 + len;
+            if rdata.end > msg.len() {
+                return Err(malformed());
+            }
+            pos = rdata.end;
+            if s == 2 && typ == TYPE_TSIG && i + 1 == count {
+                tsig = Some(parse_tsig(msg, start, name, rdata)?);
+            } else {
+                section.push(Rr {
+                    name,
+                    typ,
+                    class,
+                    rdata,
+                });
+            }
+        }
+    }
+
+    Ok(Message {
+        id,
+        flags,
+        sections,
+        tsig,
+    })
+}
+
+fn parse_tsig(
+    msg: &[u8],
+    start: usize,
+    key: String,
+    rdata: Range<usize>,
+) -> Result<Tsig, ProviderError> {
+    let (alg, p) = read_name(msg, rdata.start)?;
+    let time = (u64::from(be16(msg, p)?) << 32) | u64::from(be32(msg, p + 2)?);
+    let fudge = be16(msg, p + 6)?;
+    let mac_len = be16(msg, p + 8)? as usize;
+    let mac = msg.get(p + 10..p + 10 + mac_len).ok_or_else(malformed)?;
+    let p = p + 10 + mac_len;
+    let other_len = be16(msg, p + 4)? as usize;
+    let other = msg.get(p + 6..p + 6 + other_len).ok_or_else(malformed)?;
+    Ok(Tsig {
+        start,
+        key,
+        alg,
+        time,
+        fudge,
+        mac: mac.to_vec(),
+        orig_id: be16(msg, p)?,
+        error: be16(msg, p + 2)?,
+        other: other.to_vec(),
+    })
+}
+
+/*──────── TSIG ────────*/
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    HmacSha256,
+    HmacSha512,
+}
+
+impl Algorithm {
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::HmacSha256 => "hmac-sha256",
+            Algorithm::HmacSha512 => "hmac-sha512",
+        }
+    }
+
+    fn sign(self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        match self {
+            Algorithm::HmacSha256 => keyed::<Hmac<Sha256>>(key, data)
+                .finalize()
+                .into_bytes()
+                .to_vec(),
+            Algorithm::HmacSha512 => keyed::<Hmac<Sha512>>(key, data)
+                .finalize()
+                .into_bytes()
+                .to_vec(),
+        }
+    }
+
+    /// Constant-time MAC comparison.
+    fn verify(self, key: &[u8], data: &[u8], mac: &[u8]) -> bool {
+        match self {
+            Algorithm::HmacSha256 => keyed::<Hmac<Sha256>>(key, data).verify_slice(mac).is_ok(),
+            Algorithm::HmacSha512 => keyed::<Hmac<Sha512>>(key, data).verify_slice(mac).is_ok(),
+        }
+    }
+}
+
+fn keyed<M: Mac + KeyInit>(key: &[u8], data: &[u8]) -> M {
+    let mut m = <M as KeyInit>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    m.update(data);
+    m
+}
+
+impl FromStr for Algorithm {
+    type Err = ProviderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim_end_matches('.').to_ascii_lowercase().as_str() {
+            "hmac-sha256" => Ok(Algorithm::HmacSha256),
+            "hmac-sha512" => Ok(Algorithm::HmacSha512),
+            _ => Err(ProviderError::InvalidInput(format!(
+                "unsupported TSIG algorithm `{s}` (use hmac-sha256 or hmac-sha512)"
+            ))),
+        }
+    }
+}
+
+/// TSIG error code as text.
+pub fn tsig_error_name(code: u16) -> String {
+    match code {
+        16 => "BADSIG".into(),
+        17 => "BADKEY".into(),
+        18 => "BADTIME".into(),
+        22 => "BADTRUNC".into(),
+        n => format!("TSIG error {n}"),
+    }
+}
+
+pub struct TsigKey {
+    name: String,
+    alg: Algorithm,
+    secret: Vec<u8>,
+}
+
+impl TsigKey {
+    pub fn new(name: &str, alg: Algorithm, secret: Vec<u8>) -> Result<Self, ProviderError> {
+        let name = name.trim_end_matches('.').to_ascii_lowercase();
+        push_name(&mut Vec::new(), &name)?;
+        Ok(Self { name, alg, secret })
+    }
+
+    /// Sign `msg` and append the TSIG record; returns the MAC.
+    /// `prior` is the request MAC when signing a reply.
+    pub fn sign(&self, msg: &mut Vec<u8>, prior: Option<&[u8]>, time: u64) -> Vec<u8> {
+        let mut data = Vec::with_capacity(msg.len() + 128);
+        push_prior(&mut data, prior);
+        data.extend_from_slice(msg);
+        self.variables(&mut data, time, FUDGE, 0, &[]);
+        let mac = self.alg.sign(&self.secret, &data);
+        self.append(msg, time, &mac, 0);
+        mac
+    }
+
+    /// Append a TSIG record carrying `mac` and `error` (an empty MAC for
+    /// unsigned error replies) and bump ARCOUNT.
+    pub fn append(&self, msg: &mut Vec<u8>, time: u64, mac: &[u8], error: u16) {
+        let mut rdata = Vec::with_capacity(mac.len() + 32);
+        push_name(&mut rdata, self.alg.name()).expect("static algorithm name");
+        rdata.extend_from_slice(&time.to_be_bytes()[2..]);
+        rdata.extend_from_slice(&FUDGE.to_be_bytes());
+        rdata.extend_from_slice(&(mac.len() as u16).to_be_bytes());
+        rdata.extend_from_slice(mac);
+        rdata.extend_from_slice(&msg[0..2]);
+        rdata.extend_from_slice(&error.to_be_bytes());
+        rdata.extend_from_slice(&0u16.to_be_bytes());
+
+        push_name(msg, &self.name).expect("key name checked in TsigKey::new");
+        msg.extend_from_slice(&TYPE_TSIG.to_be_bytes());
+        msg.extend_from_slice(&CLASS_ANY.to_be_bytes());
+        msg.extend_from_slice(&0u32.to_be_bytes());
+        msg.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        msg.extend_from_slice(&rdata);
+        let ar = u16::from_be_bytes([msg[10], msg[11]]) + 1;
+        msg[10..12].copy_from_slice(&ar.to_be_bytes());
+    }
+
+    /// Check the TSIG record `tsig` parsed from `msg`; `Err` carries the TSIG
+    /// error code (BADKEY / BADSIG / BADTIME).
+    pub fn verify(
+        &self,
+        msg: &[u8],
+        tsig: &Tsig,
+        prior: Option<&[u8]>,
+        now: u64,
+    ) -> Result<(), u16> {
+        if !tsig.key.eq_ignore_ascii_case(&self.name)
+            || !tsig.alg.eq_ignore_ascii_case(self.alg.name())
+        {
+            return Err(17);
+        }
+        let mut body = msg[..tsig.start].to_vec();
+        body[0..2].copy_from_slice(&tsig.orig_id.to_be_bytes());
+        let ar = u16::from_be_bytes([body[10], body[11]]).saturating_sub(1);
+        body[10..12].copy_from_slice(&ar.to_be_bytes());
+
+        let mut data = Vec::with_capacity(body.len() + 128);
+        push_prior(&mut data, prior);
+        data.extend_from_slice(&body);
+        self.variables(&mut data, tsig.time, tsig.fudge, tsig.error, &tsig.other);
+        if !self.alg.verify(&self.secret, &data, &tsig.mac) {
+            return Err(16);
+        }
+        if now.abs_diff(tsig.time) > u64::from(tsig.fudge) {
+            return Err(18);
+        }
+        Ok(())
+    }
+
+    /// TSIG variables (RFC 8945 §4.3.3), appended to the signed data.
+    fn variables(&self, out: &mut Vec<u8>, time: u64, fudge: u16, error: u16, other: &[u8]) {
+        push_name(out, &self.name).expect("key name checked in TsigKey::new");
+        out.extend_from_slice(&CLASS_ANY.to_be_bytes());
+        out.extend_from_slice(&0u32.to_be_bytes());
+        push_name(out, self.alg.name()).expect("static algorithm name");
+        out.extend_from_slice(&time.to_be_bytes()[2..]);
+        out.extend_from_slice(&fudge.to_be_bytes());
+        out.extend_from_slice(&error.to_be_bytes());
+        out.extend_from_slice(&(other.len() as u16).to_be_bytes());
+        out.extend_from_slice(other);
+    }
+}
+
+fn push_prior(out: &mut Vec<u8>, prior: Option<&[u8]>) {
+    if let Some(mac) = prior {
+        out.extend_from_slice(&(mac.len() as u16).to_be_bytes());
+        out.extend_from_slice(mac);
+    }
+}
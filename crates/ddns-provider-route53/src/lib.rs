@@ -0,0 +1,560 @@
+//! AWS Route 53 DNS provider
+//!
+//! * Supports `A` / `AAAA` / `TXT` / `CNAME` / `HTTPS` / `SVCB` record *upsert*
+//!   through `ChangeResourceRecordSets` with the `UPSERT` action.
+//! * Requests are signed with **SigV4**; credentials from the config, the
+//!   `AWS_*` env vars or the shared credentials file.
+//! * The hosted zone is found by name (`ListHostedZonesByName`, public zones
+//!   first) and its id cached; `hosted_zone_id` skips the lookup.
+//! * API errors are mapped to [`ddns_provider::ProviderError`] by their `Code`.
+
+mod sigv4;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderCache, ProviderError, ProviderFactory, ProviderSpec,
+    ProviderTable, RecordType,
+    name::{absolute, relative},
+    parse_table, quote_txt,
+};
+use once_cell::sync::OnceCell;
+use reqwest::{Client, Method, StatusCode, header::CONTENT_TYPE};
+use serde::{Deserialize, de::DeserializeOwned};
+use sigv4::Credentials;
+use std::{sync::Arc, time::Duration};
+use tracing::{debug, info};
+
+const HOST: &str = "route53.amazonaws.com";
+const REGION: &str = "us-east-1"; // Route 53 is global; SigV4 scope is fixed
+const SERVICE: &str = "route53";
+const API: &str = "/2013-04-01";
+const XMLNS: &str = "https://route53.amazonaws.com/doc/2013-04-01/";
+const DEFAULT_TTL: u32 = 300;
+
+/// Map a Route 53 error `Code` to a [`ProviderError`].
+fn map_error(
+    status: StatusCode,
+    code: &str,
+    msg: &str,
+    retry_after: Option<Duration>,
+) -> ProviderError {
+    let text = format!("{code}: {msg}");
+    match code {
+        "InvalidClientTokenId"
+        | "SignatureDoesNotMatch"
+        | "IncompleteSignature"
+        | "MissingAuthenticationToken"
+        | "ExpiredToken"
+        | "AccessDenied"
+        | "AccessDeniedException" => ProviderError::Auth(text),
+        "NoSuchHostedZone" => ProviderError::NotFound(text),
+        "Throttling" | "ThrottlingException" | "PriorRequestNotComplete" => {
+            ProviderError::RateLimited { retry_after }
+        }
+        "ServiceUnavailable" | "InternalFailure" => ProviderError::Transient(text),
+        "InvalidChangeBatch" | "InvalidInput" | "InvalidDomainName" => {
+            ProviderError::InvalidInput(text)
+        }
+        _ => ProviderError::from_status(status, text, retry_after),
+    }
+}
+
+/*──────── XML replies ────────*/
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ErrorResponse {
+    error: ErrorBody,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ErrorBody {
+    code: String,
+    #[serde(default)]
+    message: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ZonesByName {
+    hosted_zones: HostedZones,
+}
+
+#[derive(Deserialize)]
+struct HostedZones {
+    #[serde(rename = "HostedZone", default)]
+    zones: Vec<HostedZone>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct HostedZone {
+    id: String,
+    name: String,
+    #[serde(default)]
+    config: Option<ZoneConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ZoneConfig {
+    #[serde(default)]
+    private_zone: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RecordSets {
+    resource_record_sets: RecordSetList,
+    #[serde(default)]
+    is_truncated: bool,
+    #[serde(default)]
+    next_record_name: Option<String>,
+    #[serde(default)]
+    next_record_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RecordSetList {
+    #[serde(rename = "ResourceRecordSet", default)]
+    sets: Vec<RecordSet>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RecordSet {
+    name: String,
+    #[serde(rename = "Type")]
+    typ: String,
+    #[serde(rename = "TTL", default)]
+    ttl: Option<u32>,
+    #[serde(default)]
+    resource_records: Option<ResourceRecords>,
+}
+
+#[derive(Deserialize)]
+struct ResourceRecords {
+    #[serde(rename = "ResourceRecord", default)]
+    records: Vec<ResourceRecord>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ResourceRecord {
+    value: String,
+}
+
+impl RecordSet {
+    fn values(&self) -> impl Iterator<Item = &str> {
+        self.resource_records
+            .iter()
+            .flat_map(|r| &r.records)
+            .map(|r| r.value.as_str())
+    }
+}
+
+fn parse<T: DeserializeOwned>(xml: &str) -> Result<T, ProviderError> {
+    quick_xml::de::from_str(xml).map_err(|e| ProviderError::Api(format!("bad Route 53 reply: {e}")))
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `ChangeResourceRecordSets` request body with a single change.
+fn change_batch(action: &str, name: &str, typ: &str, ttl: u32, values: &[&str]) -> String {
+    let records: String = values
+        .iter()
+        .map(|v| {
+            format!(
+                "<ResourceRecord><Value>{}</Value></ResourceRecord>",
+                xml_escape(v)
+            )
+        })
+        .collect();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><ChangeResourceRecordSetsRequest xmlns="{XMLNS}"><ChangeBatch><Comment>ddns-rs</Comment><Changes><Change><Action>{action}</Action><ResourceRecordSet><Name>{}</Name><Type>{typ}</Type><TTL>{ttl}</TTL><ResourceRecords>{records}</ResourceRecords></ResourceRecordSet></Change></Changes></ChangeBatch></ChangeResourceRecordSetsRequest>"#,
+        xml_escape(name)
+    )
+}
+
+/*──────── provider struct ────────*/
+
+pub struct Route53Provider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    creds: Credentials,
+    client: Client,
+
+    zone_id: OnceCell<String>,
+}
+
+impl Route53Provider {
+    /// Keys and profile are optional; without them the `AWS_*` env vars and
+    /// the shared credentials file are consulted.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        zone: &str,
+        record: &str,
+        record_type: &str,
+        access_key_id: Option<&str>,
+        secret_access_key: Option<&str>,
+        session_token: Option<&str>,
+        profile: Option<&str>,
+        hosted_zone_id: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let zone_id = OnceCell::new();
+        if let Some(id) = hosted_zone_id {
+            let _ = zone_id.set(id.trim_start_matches("/hostedzone/").to_owned());
+        }
+        Ok(Self {
+            zone_name: zone.trim_end_matches('.').to_owned(),
+            record_name: record.to_owned(),
+            rtype: record_type.parse()?,
+            creds: Credentials::resolve(access_key_id, secret_access_key, session_token, profile)?,
+            client: Client::new(),
+            zone_id,
+        })
+    }
+
+    /*──────── signed request helper ────────*/
+
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: String,
+    ) -> Result<String, ProviderError> {
+        let req = sigv4::Request {
+            method: method.as_str(),
+            host: HOST,
+            path,
+            query,
+            headers: &[],
+            payload: body.as_bytes(),
+        };
+        let signed = sigv4::sign(&self.creds, REGION, SERVICE, &req, Utc::now());
+        let qs = sigv4::canonical_query(query);
+        let url = if qs.is_empty() {
+            format!("https://{HOST}{path}")
+        } else {
+            format!("https://{HOST}{path}?{qs}")
+        };
+
+        let mut rb = self.client.request(method, url);
+        for (k, v) in signed {
+            rb = rb.header(k, v);
+        }
+        if !body.is_empty() {
+            rb = rb.header(CONTENT_TYPE, "text/xml");
+        }
+        let resp = rb.body(body).send().await?;
+        let status = resp.status();
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        let text = resp.text().await?;
+        if status.is_success() {
+            return Ok(text);
+        }
+        Err(match quick_xml::de::from_str::<ErrorResponse>(&text) {
+            Ok(e) => map_error(status, &e.error.code, &e.error.message, retry_after),
+            Err(_) => ProviderError::from_status(status, format!("HTTP {status}"), retry_after),
+        })
+    }
+
+    /*──────── zone / record helpers ────────*/
+
+    async fn zone_id_for(&self, zone: &str) -> Result<String, ProviderError> {
+        let own = zone
+            .trim_end_matches('.')
+            .eq_ignore_ascii_case(&self.zone_name);
+        if own && let Some(id) = self.zone_id.get() {
+            return Ok(id.clone());
+        }
+        let want = absolute("@", zone);
+        let xml = self
+            .send(
+                Method::GET,
+                &format!("{API}/hostedzonesbyname"),
+                &[("dnsname", want.clone()), ("maxitems", "10".into())],
+                String::new(),
+            )
+            .await?;
+        let r: ZonesByName = parse(&xml)?;
+        let id = r
+            .hosted_zones
+            .zones
+            .iter()
+            .filter(|z| z.name.eq_ignore_ascii_case(&want))
+            // public zones first
+            .min_by_key(|z| z.config.as_ref().is_some_and(|c| c.private_zone))
+            .map(|z| z.id.trim_start_matches("/hostedzone/").to_owned())
+            .ok_or_else(|| ProviderError::NotFound(format!("hosted zone {zone}")))?;
+        if own {
+            let _ = self.zone_id.set(id.clone());
+        }
+        Ok(id)
+    }
+
+    /// One page of record sets starting at `name` / `typ`.
+    async fn record_sets(
+        &self,
+        zone_id: &str,
+        start: Option<(&str, &str)>,
+        max: u32,
+    ) -> Result<RecordSets, ProviderError> {
+        let mut q = vec![("maxitems", max.to_string())];
+        if let Some((name, typ)) = start {
+            q.push(("name", name.to_owned()));
+            q.push(("type", typ.to_owned()));
+        }
+        let xml = self
+            .send(
+                Method::GET,
+                &format!("{API}/hostedzone/{zone_id}/rrset"),
+                &q,
+                String::new(),
+            )
+            .await?;
+        parse(&xml)
+    }
+
+    /// The record set `name` / `typ`, if present.
+    async fn find_set(
+        &self,
+        zone_id: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<RecordSet>, ProviderError> {
+        let page = self
+            .record_sets(zone_id, Some((name, typ.as_str())), 1)
+            .await?;
+        Ok(page
+            .resource_record_sets
+            .sets
+            .into_iter()
+            .find(|s| s.name.eq_ignore_ascii_case(name) && s.typ == typ.as_str()))
+    }
+
+    async fn change(&self, zone_id: &str, body: String) -> Result<(), ProviderError> {
+        self.send(
+            Method::POST,
+            &format!("{API}/hostedzone/{zone_id}/rrset/"),
+            &[],
+            body,
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct Route53Cfg {
+    #[serde(default)]
+    access_key_id: Option<String>,
+    #[serde(default)]
+    secret_access_key: Option<String>,
+    #[serde(default)]
+    session_token: Option<String>,
+    #[serde(default)]
+    profile: Option<String>,
+    #[serde(default)]
+    hosted_zone_id: Option<String>,
+}
+
+/// Builds `kind = "route53"` entries; all keys are optional
+/// (`access_key_id` + `secret_access_key` [+ `session_token`], `profile`,
+/// `hosted_zone_id`).
+pub struct Route53Factory;
+
+impl ProviderFactory for Route53Factory {
+    fn kind(&self) -> &'static str {
+        "route53"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: Route53Cfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(Route53Provider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            c.access_key_id.as_deref(),
+            c.secret_access_key.as_deref(),
+            c.session_token.as_deref(),
+            c.profile.as_deref(),
+            c.hosted_zone_id.as_deref(),
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for Route53Provider {
+    fn name(&self) -> &'static str {
+        "Route53"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    fn cache(&self) -> ProviderCache {
+        let mut c = ProviderCache::new();
+        if let Some(id) = self.zone_id.get() {
+            c.insert("zone_id".into(), id.clone());
+        }
+        c
+    }
+
+    fn restore_cache(&self, cache: &ProviderCache) {
+        if let Some(id) = cache.get("zone_id") {
+            let _ = self.zone_id.set(id.clone());
+        }
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        let set = self.find_set(&zid, &absolute(name, zone), typ).await?;
+        Ok(set.and_then(|s| s.values().next().map(str::to_owned)))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        let mut out = Vec::new();
+        let mut next: Option<(String, String)> = None;
+        loop {
+            let page = self
+                .record_sets(
+                    &zid,
+                    next.as_ref().map(|(n, t)| (n.as_str(), t.as_str())),
+                    300,
+                )
+                .await?;
+            for s in &page.resource_record_sets.sets {
+                let Ok(typ) = s.typ.parse::<RecordType>() else {
+                    continue;
+                };
+                for v in s.values() {
+                    out.push(DnsRecord {
+                        id: format!("{}/{}", s.name, s.typ),
+                        name: relative(&s.name, zone),
+                        typ,
+                        value: v.to_owned(),
+                        ttl: s.ttl.unwrap_or_default(),
+                    });
+                }
+            }
+            match (
+                page.is_truncated,
+                page.next_record_name,
+                page.next_record_type,
+            ) {
+                (true, Some(n), Some(t)) => next = Some((n, t)),
+                _ => break,
+            }
+        }
+        Ok(out)
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        let fqdn = absolute(name, zone);
+        // DELETE has to repeat the record set exactly
+        let set = self
+            .find_set(&zid, &fqdn, typ)
+            .await?
+            .ok_or_else(|| ProviderError::NotFound(format!("{typ} {fqdn}")))?;
+        let values: Vec<&str> = set.values().collect();
+        let body = change_batch(
+            "DELETE",
+            &set.name,
+            &set.typ,
+            set.ttl.unwrap_or_default(),
+            &values,
+        );
+        self.change(&zid, body).await?;
+        info!("Route53 deleted {typ} {fqdn}");
+        Ok(())
+    }
+
+    async fn upsert_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+        ip: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        let zid = self.zone_id_for(zone).await?;
+        let fqdn = absolute(name, zone);
+        // Route 53 stores TXT data as quoted character-strings
+        let value = if typ == RecordType::TXT {
+            quote_txt(ip)
+        } else {
+            ip.to_owned()
+        };
+        let ttl = if ttl == 0 { DEFAULT_TTL } else { ttl };
+        self.change(
+            &zid,
+            change_batch("UPSERT", &fqdn, typ.as_str(), ttl, &[&value]),
+        )
+        .await?;
+        debug!("Route53 upsert {fqdn} -> {ip}");
+        Ok(())
+    }
+}
+
+/*──────── optional live-test (ignored by default) ────────*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Uses the standard `AWS_*` env vars or `~/.aws/credentials`.
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn live_upsert() {
+        let r53 = Route53Provider::new(
+            "example.com",
+            "test-ddns",
+            "A",
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        r53.upsert_record("example.com", "test-ddns", RecordType::A, "1.1.1.1", 60)
+            .await
+            .unwrap();
+    }
+}
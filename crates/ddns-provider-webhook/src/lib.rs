@@ -0,0 +1,405 @@
+//! Generic HTTP / webhook provider
+//!
+//! * One configurable request per *upsert*: `method`, `url`, `headers` and
+//!   `body` are templates over `{zone}`, `{record}`, `{fqdn}`, `{type}`,
+//!   `{ip}` and `{ttl}`.
+//! * Placeholders in the URL are percent-encoded; in headers and body they
+//!   are inserted verbatim.
+//! * Success means a status listed in `success_status` (default: any 2xx) and,
+//!   when `success_pointer` is set, a JSON reply whose value at that pointer
+//!   equals `success_value` (default: `true`).
+//! * Write-only: `get_record` keeps the trait default (`Unsupported`), so the
+//!   scheduler skips its read-before-write.
+
+use async_trait::async_trait;
+use ddns_provider::{
+    DnsProvider, ProviderError, ProviderFactory, ProviderSpec, ProviderTable, RecordType,
+    name::fqdn, parse_table,
+};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use reqwest::{
+    Client, Method, Url,
+    header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT},
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+use tracing::{debug, info};
+
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+
+/// Everything but the RFC 3986 unreserved characters.
+const UNRESERVED: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/*──────── templates ────────*/
+
+/// Values the placeholders stand for in one request.
+struct Vars<'a> {
+    zone: &'a str,
+    record: &'a str,
+    typ: RecordType,
+    ip: &'a str,
+    ttl: u32,
+}
+
+impl Vars<'_> {
+    /// `template` with every placeholder replaced; `encode` percent-encodes
+    /// the inserted values (for URLs).
+    fn render(&self, template: &str, encode: bool) -> String {
+        let fqdn = fqdn(self.record, self.zone);
+        let ttl = self.ttl.to_string();
+        let pairs = [
+            ("{zone}", self.zone),
+            ("{record}", self.record),
+            ("{fqdn}", fqdn.as_str()),
+            ("{type}", self.typ.as_str()),
+            ("{ip}", self.ip),
+            ("{ttl}", ttl.as_str()),
+        ];
+        let mut out = template.to_owned();
+        for (k, v) in pairs {
+            if encode {
+                out = out.replace(k, &utf8_percent_encode(v, UNRESERVED).to_string());
+            } else {
+                out = out.replace(k, v);
+            }
+        }
+        out
+    }
+}
+
+/*──────── success check ────────*/
+
+/// Verdict on a reply `body`: `None` when accepted, otherwise the reason.
+fn pointer_mismatch(body: &str, pointer: &str, expected: &Value) -> Option<String> {
+    let Ok(v) = serde_json::from_str::<Value>(body) else {
+        return Some(format!(
+            "reply is not JSON (expected `{pointer}` = {expected})"
+        ));
+    };
+    match v.pointer(pointer) {
+        Some(actual) if actual == expected => None,
+        // a number / bool in the config also matches its string form (`200` ↔ `"200"`)
+        Some(Value::String(s))
+            if !expected.is_string()
+                && serde_json::from_str::<Value>(s).ok().as_ref() == Some(expected) =>
+        {
+            None
+        }
+        Some(actual) => Some(format!("`{pointer}` = {actual}, expected {expected}")),
+        None => Some(format!("`{pointer}` missing in reply")),
+    }
+}
+
+/*──────── provider struct ────────*/
+
+pub struct WebhookProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    method: String,
+    url: String,
+    headers: BTreeMap<String, String>,
+    body: Option<String>,
+    success_status: Vec<u16>,
+    /// JSON pointer and the value expected there
+    success_check: Option<(String, Value)>,
+    client: Client,
+}
+
+impl WebhookProvider {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        zone: &str,
+        record: &str,
+        rtype: &str,
+        method: &str,
+        url: &str,
+        headers: BTreeMap<String, String>,
+        body: Option<String>,
+        success_status: Vec<u16>,
+        success_check: Option<(String, Value)>,
+        timeout: Duration,
+    ) -> anyhow::Result<Self> {
+        if let Some((p, _)) = &success_check
+            && !p.is_empty()
+            && !p.starts_with('/')
+        {
+            anyhow::bail!("webhook: `success_pointer` must be empty or start with `/`, got `{p}`");
+        }
+        let rtype: RecordType = rtype.parse()?;
+
+        // catch config typos here instead of retrying them on every tick
+        let sample = Vars {
+            zone,
+            record,
+            typ: rtype,
+            ip: if rtype == RecordType::AAAA {
+                "2001:db8::1"
+            } else {
+                "192.0.2.1"
+            },
+            ttl: 300,
+        };
+        let m = sample.render(method, false).to_ascii_uppercase();
+        Method::from_bytes(m.as_bytes())
+            .map_err(|_| anyhow::anyhow!("webhook: bad method `{method}`"))?;
+        Url::parse(&sample.render(url, true))
+            .map_err(|e| anyhow::anyhow!("webhook: bad url `{url}`: {e}"))?;
+        for (k, v) in &headers {
+            HeaderName::from_bytes(k.as_bytes())
+                .map_err(|_| anyhow::anyhow!("webhook: bad header name `{k}`"))?;
+            HeaderValue::from_str(&sample.render(v, false))
+                .map_err(|_| anyhow::anyhow!("webhook: bad value for header `{k}`"))?;
+        }
+
+        let mut hdr = HeaderMap::new();
+        hdr.insert(USER_AGENT, HeaderValue::from_static("ddns-rs (+github)"));
+
+        Ok(Self {
+            zone_name: zone.to_owned(),
+            record_name: record.to_owned(),
+            rtype,
+            method: method.to_owned(),
+            url: url.to_owned(),
+            headers,
+            body,
+            success_status,
+            success_check,
+            client: Client::builder()
+                .default_headers(hdr)
+                .timeout(timeout)
+                .build()?,
+        })
+    }
+
+    fn status_ok(&self, status: u16) -> bool {
+        if self.success_status.is_empty() {
+            (200..300).contains(&status)
+        } else {
+            self.success_status.contains(&status)
+        }
+    }
+
+    /*──────── the request ────────*/
+
+    async fn fire(&self, vars: &Vars<'_>) -> Result<(), ProviderError> {
+        let method = vars.render(&self.method, false).to_ascii_uppercase();
+        let method = Method::from_bytes(method.as_bytes())
+            .map_err(|_| ProviderError::InvalidInput(format!("webhook: bad method `{method}`")))?;
+        let url = vars.render(&self.url, true);
+
+        let mut req = self.client.request(method.clone(), &url);
+        for (k, v) in &self.headers {
+            req = req.header(k.as_str(), vars.render(v, false));
+        }
+        if let Some(b) = &self.body {
+            req = req.body(vars.render(b, false));
+        }
+
+        // what is left here depends on the pushed value (e.g. a header value
+        // it makes invalid); retrying the same value cannot help
+        let req = req
+            .build()
+            .map_err(|e| ProviderError::InvalidInput(format!("webhook: {method} {url}: {e}")))?;
+        let resp = self.client.execute(req).await?;
+        let status = resp.status();
+        let retry_after = ddns_provider::retry_after(resp.headers());
+        let text = resp.text().await.unwrap_or_default();
+        if !self.status_ok(status.as_u16()) {
+            let msg = if text.trim().is_empty() {
+                format!("{method} {url}: HTTP {status}")
+            } else {
+                format!("{method} {url}: HTTP {status}: {}", text.trim())
+            };
+            // a non-error status that is simply not listed is an API failure
+            return Err(if status.is_success() {
+                ProviderError::Api(msg)
+            } else {
+                ProviderError::from_status(status, msg, retry_after)
+            });
+        }
+        if let Some((pointer, expected)) = &self.success_check
+            && let Some(why) = pointer_mismatch(&text, pointer, expected)
+        {
+            return Err(ProviderError::Api(format!("{method} {url}: {why}")));
+        }
+        info!("Webhook {method} {url} -> {status}");
+        Ok(())
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct WebhookCfg {
+    url: String,
+    #[serde(default = "default_method")]
+    method: String,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    success_status: Vec<u16>,
+    #[serde(default)]
+    success_pointer: Option<String>,
+    #[serde(default)]
+    success_value: Option<Value>,
+    /// timeout in milliseconds
+    #[serde(default)]
+    timeout: Option<u64>,
+}
+
+fn default_method() -> String {
+    "GET".into()
+}
+
+/// Builds `kind = "webhook"` entries; expects `url`, optionally `method`,
+/// `headers`, `body`, `success_status`, `success_pointer`, `success_value`
+/// and `timeout` (ms).
+pub struct WebhookFactory;
+
+impl ProviderFactory for WebhookFactory {
+    fn kind(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: WebhookCfg = parse_table(self.kind(), table)?;
+        let check = match (c.success_pointer, c.success_value) {
+            (Some(p), v) => Some((p, v.unwrap_or(Value::Bool(true)))),
+            (None, Some(_)) => anyhow::bail!("webhook: `success_value` needs `success_pointer`"),
+            (None, None) => None,
+        };
+        Ok(Arc::new(WebhookProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            &c.method,
+            &c.url,
+            c.headers,
+            c.body,
+            c.success_status,
+            check,
+            Duration::from_millis(c.timeout.unwrap_or(DEFAULT_TIMEOUT_MS)),
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for WebhookProvider {
+    fn name(&self) -> &'static str {
+        "Webhook"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    async fn upsert_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+        ip: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        self.fire(&Vars {
+            zone,
+            record: name,
+            typ,
+            ip,
+            ttl,
+        })
+        .await?;
+        debug!("Webhook upsert {name}.{zone} -> {ip}");
+        Ok(())
+    }
+}
+
+/*──────── tests ────────*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn templates() {
+        let v = Vars {
+            zone: "example.com",
+            record: "home",
+            typ: RecordType::TXT,
+            ip: "a b&c",
+            ttl: 60,
+        };
+        assert_eq!(
+            v.render("https://h/u?host={fqdn}&t={type}&v={ip}", true),
+            "https://h/u?host=home.example.com&t=TXT&v=a%20b%26c"
+        );
+        assert_eq!(
+            v.render(
+                r#"{"zone":"{zone}","name":"{record}","ttl":{ttl},"v":"{ip}"}"#,
+                false
+            ),
+            r#"{"zone":"example.com","name":"home","ttl":60,"v":"a b&c"}"#
+        );
+        let apex = Vars { record: "@", ..v };
+        assert_eq!(apex.render("{fqdn}", false), "example.com");
+    }
+
+    #[test]
+    fn success_pointer() {
+        let t = Value::Bool(true);
+        assert_eq!(pointer_mismatch(r#"{"ok":true}"#, "/ok", &t), None);
+        assert_eq!(
+            pointer_mismatch(r#"{"result":{"code":"200"}}"#, "/result/code", &json!(200)),
+            None
+        );
+        assert_eq!(
+            pointer_mismatch(r#"{"status":"ok"}"#, "/status", &json!("ok")),
+            None
+        );
+        assert!(pointer_mismatch(r#"{"ok":false}"#, "/ok", &t).is_some());
+        assert!(pointer_mismatch(r#"{}"#, "/ok", &t).is_some());
+        assert!(pointer_mismatch("good", "/ok", &t).is_some());
+    }
+
+    #[test]
+    fn config_typos_fail_at_build() {
+        let build = |method: &str, url: &str, header: (&str, &str)| {
+            WebhookProvider::new(
+                "example.com",
+                "home",
+                "A",
+                method,
+                url,
+                BTreeMap::from([(header.0.to_owned(), header.1.to_owned())]),
+                None,
+                Vec::new(),
+                None,
+                Duration::from_secs(1),
+            )
+        };
+        assert!(build("POST", "https://h/{fqdn}?ip={ip}", ("X-Ip", "{ip}")).is_ok());
+        assert!(build("PO ST", "https://h/", ("X-Ip", "{ip}")).is_err());
+        assert!(build("GET", "h/{fqdn}", ("X-Ip", "{ip}")).is_err());
+        assert!(build("GET", "https://h/", ("X Ip", "{ip}")).is_err());
+        assert!(build("GET", "https://h/", ("X-Ip", "a\nb")).is_err());
+    }
+}
@@ -0,0 +1,405 @@
+//! Zone-file writer provider
+//!
+//! * Edits a BIND-style master file in place: the target rrset is replaced
+//!   (or inserted next to the owner's other records), every other line is
+//!   kept verbatim.
+//! * Each change bumps the SOA serial (`date` → `YYYYMMDDnn`, or
+//!   `increment`) and is written atomically (`<path>.tmp` + rename, file
+//!   mode preserved).
+//! * An optional `reload` command (e.g. `rndc reload example.com`) runs after
+//!   each write. While a reload is owed, the record reads as missing so the
+//!   next cycle writes again and repeats the reload even though the file is
+//!   already up to date.
+
+mod zone;
+
+pub use zone::SerialMode;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use ddns_provider::{
+    DnsProvider, DnsRecord, ProviderError, ProviderFactory, ProviderSpec, ProviderTable,
+    RecordType,
+    file::{EDIT_LOCK, io_error, write_atomic},
+    name::absolute,
+    parse_table, record_value,
+};
+use serde::Deserialize;
+use std::{
+    fs,
+    path::PathBuf,
+    process::Stdio,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use tokio::{process::Command, time::timeout};
+use tracing::{debug, info};
+use zone::Zone;
+
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+
+/*──────── provider struct ────────*/
+
+pub struct ZoneFileProvider {
+    zone_name: String,
+    record_name: String,
+    rtype: RecordType,
+    path: PathBuf,
+    serial: SerialMode,
+    reload: Vec<String>,
+    timeout: Duration,
+
+    /// the last reload failed; retry it even if the file is unchanged
+    reload_pending: AtomicBool,
+}
+
+impl ZoneFileProvider {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        zone: &str,
+        record: &str,
+        record_type: &str,
+        path: impl Into<PathBuf>,
+        serial: SerialMode,
+        reload: Vec<String>,
+        timeout: Duration,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            zone_name: zone.trim_end_matches('.').to_owned(),
+            record_name: record.to_owned(),
+            rtype: record_type.parse()?,
+            path: path.into(),
+            serial,
+            reload,
+            timeout,
+            reload_pending: AtomicBool::new(false),
+        })
+    }
+
+    /*──────── file helpers ────────*/
+
+    fn load(&self, zone: &str) -> Result<Zone, ProviderError> {
+        let text = fs::read_to_string(&self.path).map_err(|e| io_error(&self.path, e))?;
+        Ok(Zone::parse(&text, zone))
+    }
+
+    /// Apply `edit` under the lock; bump the serial and write the file when
+    /// it reports a change. Returns `edit`'s result.
+    fn modify<T>(
+        &self,
+        zone: &str,
+        edit: impl FnOnce(&mut Zone) -> Result<(bool, T), ProviderError>,
+    ) -> Result<(bool, T), ProviderError> {
+        let _guard = EDIT_LOCK.lock().expect("edit lock");
+        let mut z = self.load(zone)?;
+        let (changed, out) = edit(&mut z)?;
+        if changed {
+            let serial = z
+                .bump_serial(self.serial, Utc::now().date_naive())
+                .ok_or_else(|| {
+                    ProviderError::InvalidInput(format!(
+                        "`{}`: no SOA record with a numeric serial",
+                        self.path.display()
+                    ))
+                })?;
+            write_atomic(&self.path, &z.render(), false).map_err(|e| io_error(&self.path, e))?;
+            info!("ZoneFile wrote `{}` (serial {serial})", self.path.display());
+        }
+        Ok((changed, out))
+    }
+
+    /// Run the `reload` command (if any) after a change, or to retry a
+    /// failed one.
+    async fn reload(&self, changed: bool) -> Result<(), ProviderError> {
+        let Some((cmd, args)) = self.reload.split_first() else {
+            return Ok(());
+        };
+        if !changed && !self.reload_pending.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        self.reload_pending.store(true, Ordering::Relaxed);
+        let child = Command::new(cmd)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| ProviderError::InvalidInput(format!("spawn `{cmd}`: {e}")))?;
+        let out = match timeout(self.timeout, child.wait_with_output()).await {
+            Ok(res) => res.map_err(|e| ProviderError::Transient(e.to_string()))?,
+            Err(_) => {
+                return Err(ProviderError::Transient(format!(
+                    "`{cmd}` timed out after {}ms",
+                    self.timeout.as_millis()
+                )));
+            }
+        };
+        if !out.status.success() {
+            let stderr = String::from_utf8_lossy(&out.stderr).trim().to_owned();
+            return Err(ProviderError::Transient(if stderr.is_empty() {
+                format!("`{cmd}` exited with {}", out.status)
+            } else {
+                format!("`{cmd}`: {stderr}")
+            }));
+        }
+        self.reload_pending.store(false, Ordering::Relaxed);
+        debug!("ZoneFile ran `{}`", self.reload.join(" "));
+        Ok(())
+    }
+}
+
+/*──────── factory ────────*/
+
+#[derive(Deserialize)]
+struct ZoneFileCfg {
+    path: PathBuf,
+    #[serde(default)]
+    serial: SerialCfg,
+    #[serde(default)]
+    reload: Vec<String>,
+    /// reload timeout in milliseconds
+    #[serde(default)]
+    timeout: Option<u64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+enum SerialCfg {
+    #[default]
+    Date,
+    Increment,
+}
+
+/// Builds `kind = "zonefile"` entries; expects `path`, optionally `serial`
+/// (`date` / `increment`), `reload` (argv) and `timeout` (ms).
+pub struct ZoneFileFactory;
+
+impl ProviderFactory for ZoneFileFactory {
+    fn kind(&self) -> &'static str {
+        "zonefile"
+    }
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>> {
+        let c: ZoneFileCfg = parse_table(self.kind(), table)?;
+        Ok(Arc::new(ZoneFileProvider::new(
+            spec.zone,
+            spec.record,
+            spec.record_type,
+            c.path,
+            match c.serial {
+                SerialCfg::Date => SerialMode::Date,
+                SerialCfg::Increment => SerialMode::Increment,
+            },
+            c.reload,
+            Duration::from_millis(c.timeout.unwrap_or(DEFAULT_TIMEOUT_MS)),
+        )?))
+    }
+}
+
+/*──────── DnsProvider impl ────────*/
+
+#[async_trait]
+impl DnsProvider for ZoneFileProvider {
+    fn name(&self) -> &'static str {
+        "ZoneFile"
+    }
+    fn zone(&self) -> &str {
+        &self.zone_name
+    }
+    fn record(&self) -> &str {
+        &self.record_name
+    }
+    fn record_type(&self) -> RecordType {
+        self.rtype
+    }
+
+    async fn get_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        // the file may be current while the server still serves the old data
+        if self.reload_pending.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        Ok(self
+            .load(zone)?
+            .find(&absolute(name, zone), typ.as_str())
+            .first()
+            .map(|e| e.value()))
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        let z = self.load(zone)?;
+        Ok(z.entries()
+            .iter()
+            .filter_map(|e| {
+                let typ = e.typ.parse().ok()?;
+                let name = z.name_of(e);
+                Some(DnsRecord {
+                    id: format!("{name}/{typ}"),
+                    name,
+                    typ,
+                    value: e.value(),
+                    ttl: e.ttl.unwrap_or_default(),
+                })
+            })
+            .collect())
+    }
+
+    async fn delete_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        let owner = absolute(name, zone);
+        let (changed, _) = self.modify(zone, |z| Ok((z.delete(&owner, typ.as_str()) > 0, ())))?;
+        if !changed {
+            return Err(ProviderError::NotFound(format!("{typ} {owner}")));
+        }
+        self.reload(true).await?;
+        info!("ZoneFile deleted {typ} {owner}");
+        Ok(())
+    }
+
+    async fn upsert_record(
+        &self,
+        zone: &str,
+        name: &str,
+        typ: RecordType,
+        ip: &str,
+        ttl: u32,
+    ) -> Result<(), ProviderError> {
+        let owner = absolute(name, zone);
+        let value = record_value(typ, ip);
+        let ttl = (ttl > 0).then_some(ttl);
+        let (changed, _) =
+            self.modify(zone, |z| Ok((z.set(&owner, typ.as_str(), &value, ttl), ())))?;
+        self.reload(changed).await?;
+        debug!("ZoneFile upsert {owner} -> {ip} (changed: {changed})");
+        Ok(())
+    }
+}
+
+/*──────── local tests (temp dir, POSIX `sh`) ────────*/
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::env;
+
+    const ZONE: &str = "\
+$TTL 3600
+@    IN SOA ns1.example.com. hostmaster.example.com. 7 7200 3600 1209600 300
+     IN NS  ns1.example.com.
+home IN A   192.0.2.7
+";
+
+    #[tokio::test]
+    async fn upsert_bumps_serial_and_reloads() {
+        let dir = env::temp_dir().join(format!("ddns-zonefile-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("example.com.zone");
+        let marker = dir.join("reloaded");
+        fs::write(&path, ZONE).unwrap();
+
+        let p = ZoneFileProvider::new(
+            "example.com",
+            "home",
+            "A",
+            &path,
+            SerialMode::Increment,
+            vec![
+                "sh".into(),
+                "-c".into(),
+                format!("echo x >> {}", marker.display()),
+            ],
+            Duration::from_secs(5),
+        )
+        .unwrap();
+
+        p.upsert_record("example.com", "home", RecordType::A, "192.0.2.8", 0)
+            .await
+            .unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("hostmaster.example.com. 8 7200"));
+        assert!(text.contains("home\tIN\tA\t192.0.2.8\n"));
+        assert_eq!(
+            p.get_record("example.com", "home", RecordType::A)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("192.0.2.8")
+        );
+
+        // unchanged → no write, no reload
+        p.upsert_record("example.com", "home", RecordType::A, "192.0.2.8", 0)
+            .await
+            .unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains(" 8 7200"));
+        assert_eq!(fs::read_to_string(&marker).unwrap().lines().count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_reload_is_retried_next_cycle() {
+        let dir = env::temp_dir().join(format!("ddns-zonefile-reload-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("example.com.zone");
+        let (armed, marker) = (dir.join("armed"), dir.join("reloaded"));
+        fs::write(&path, ZONE).unwrap();
+
+        // fails the first time, succeeds afterwards
+        let script = format!(
+            "if [ -e {armed} ]; then echo x >> {marker}; else touch {armed}; exit 1; fi",
+            armed = armed.display(),
+            marker = marker.display()
+        );
+        let p = ZoneFileProvider::new(
+            "example.com",
+            "home",
+            "A",
+            &path,
+            SerialMode::Increment,
+            vec!["sh".into(), "-c".into(), script],
+            Duration::from_secs(5),
+        )
+        .unwrap();
+
+        let err = p
+            .upsert_record("example.com", "home", RecordType::A, "192.0.2.8", 0)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProviderError::Transient(_)));
+        assert!(fs::read_to_string(&path).unwrap().contains("192.0.2.8"));
+
+        // next cycle: the scheduler's read must not report "up to date" …
+        let live = p.get_record("example.com", "home", RecordType::A).await;
+        assert_eq!(live.unwrap(), None);
+        // … so it writes again, which repeats the reload without a new serial
+        p.upsert_record("example.com", "home", RecordType::A, "192.0.2.8", 0)
+            .await
+            .unwrap();
+        assert_eq!(fs::read_to_string(&marker).unwrap().lines().count(), 1);
+        assert!(fs::read_to_string(&path).unwrap().contains(" 8 7200"));
+        assert_eq!(
+            p.get_record("example.com", "home", RecordType::A)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("192.0.2.8")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
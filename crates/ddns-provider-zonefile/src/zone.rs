@@ -0,0 +1,451 @@
+//! Minimal master-file (RFC 1035 §5) editing
+//!
+//! * Understands `$ORIGIN`, blank (inherited) owners, optional TTL / class,
+//!   comments, quoted strings and parenthesised multi-line records (the SOA).
+//! * Edits are line based: every line not touched is written back verbatim.
+//! * `$INCLUDE`d files are not followed; records in them are invisible.
+
+use chrono::{Datelike, NaiveDate};
+
+/// How [`Zone::bump_serial`] advances the SOA serial.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SerialMode {
+    /// `YYYYMMDDnn`; falls back to +1 once `nn` is exhausted or the serial
+    /// is already ahead of today.
+    Date,
+    /// Plain +1 (RFC 1982 wrap-around).
+    Increment,
+}
+
+/// Serial following `old`.
+pub fn next_serial(old: u32, mode: SerialMode, today: NaiveDate) -> u32 {
+    match mode {
+        SerialMode::Increment => old.wrapping_add(1),
+        SerialMode::Date => {
+            let base = today.year() as u32 * 1_000_000 + today.month() * 10_000 + today.day() * 100;
+            if old < base {
+                base
+            } else {
+                old.wrapping_add(1)
+            }
+        }
+    }
+}
+
+/*──────── tokenizer ────────*/
+
+/// One token of a line: byte range and text.
+#[derive(Clone, Debug)]
+struct Token {
+    line: usize,
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+/// Tokens of `line`, the change in parenthesis depth and where a trailing
+/// comment starts.
+fn tokenize(idx: usize, line: &str) -> (Vec<Token>, i32, Option<usize>) {
+    let b = line.as_bytes();
+    let (mut toks, mut depth, mut i) = (Vec::new(), 0, 0);
+    while i < b.len() {
+        match b[i] {
+            c if c.is_ascii_whitespace() => i += 1,
+            b';' => return (toks, depth, Some(i)),
+            b'(' => {
+                depth += 1;
+                i += 1;
+            }
+            b')' => {
+                depth -= 1;
+                i += 1;
+            }
+            b'"' => {
+                let start = i;
+                i += 1;
+                while i < b.len() && b[i] != b'"' {
+                    i += if b[i] == b'\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(b.len());
+                toks.push(Token {
+                    line: idx,
+                    start,
+                    end: i,
+                    text: line[start..i].to_owned(),
+                });
+            }
+            _ => {
+                let start = i;
+                while i < b.len() && !b[i].is_ascii_whitespace() && !b";()\"".contains(&b[i]) {
+                    i += if b[i] == b'\\' { 2 } else { 1 };
+                }
+                i = i.min(b.len());
+                toks.push(Token {
+                    line: idx,
+                    start,
+                    end: i,
+                    text: line[start..i].to_owned(),
+                });
+            }
+        }
+    }
+    (toks, depth, None)
+}
+
+/// `name` made absolute against `origin` (lower-case, trailing dot).
+fn absolute(name: &str, origin: &str) -> String {
+    let name = name.to_ascii_lowercase();
+    if name == "@" {
+        origin.to_owned()
+    } else if name.ends_with('.') {
+        name
+    } else {
+        format!("{name}.{origin}")
+    }
+}
+
+fn is_class(tok: &str) -> bool {
+    ["IN", "CH", "HS", "CS"]
+        .iter()
+        .any(|c| tok.eq_ignore_ascii_case(c))
+}
+
+/*──────── zone ────────*/
+
+/// One resource record of the file.
+#[derive(Clone, Debug)]
+pub struct Entry {
+    first_line: usize,
+    last_line: usize,
+    /// the owner was written on the line (not inherited)
+    explicit_owner: bool,
+    /// end of the owner token on `first_line` (0 when inherited)
+    owner_end: usize,
+    /// `$ORIGIN` in effect
+    origin: String,
+    comment: Option<String>,
+    /// absolute, lower-case
+    pub owner: String,
+    pub ttl: Option<u32>,
+    /// TTL as written (`1h` style units included)
+    ttl_text: Option<String>,
+    /// upper-case mnemonic
+    pub typ: String,
+    rdata: Vec<Token>,
+}
+
+impl Entry {
+    /// Record data as written, tokens joined by one space.
+    pub fn value(&self) -> String {
+        self.rdata
+            .iter()
+            .map(|t| t.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+pub struct Zone {
+    lines: Vec<String>,
+    /// initial `$ORIGIN` (the zone apex, absolute)
+    origin: String,
+}
+
+impl Zone {
+    pub fn parse(text: &str, zone: &str) -> Self {
+        Self {
+            lines: text.split('\n').map(str::to_owned).collect(),
+            origin: format!("{}.", zone.trim_end_matches('.').to_ascii_lowercase()),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Every record, in file order.
+    pub fn entries(&self) -> Vec<Entry> {
+        let mut out = Vec::new();
+        let mut origin = self.origin.clone();
+        let mut last_owner = origin.clone();
+        let mut i = 0;
+        while i < self.lines.len() {
+            let line = &self.lines[i];
+            let (mut toks, mut depth, comment) = tokenize(i, line);
+            let first_line = i;
+            let mut comment = comment.map(|c| line[c..].to_owned());
+            i += 1;
+            if toks.is_empty() {
+                continue;
+            }
+            if toks[0].text.starts_with('$') {
+                if toks[0].text.eq_ignore_ascii_case("$ORIGIN")
+                    && let Some(o) = toks.get(1)
+                {
+                    origin = absolute(&o.text, &origin);
+                }
+                continue;
+            }
+            // multi-line record: read on until the parentheses close
+            while depth > 0 && i < self.lines.len() {
+                let (more, d, _) = tokenize(i, &self.lines[i]);
+                toks.extend(more);
+                depth += d;
+                comment = None;
+                i += 1;
+            }
+
+            let explicit_owner = !line.starts_with(|c: char| c.is_ascii_whitespace());
+            let mut rest = toks.into_iter();
+            let (owner, owner_end) = if explicit_owner {
+                match rest.next() {
+                    Some(t) => (absolute(&t.text, &origin), t.end),
+                    None => continue,
+                }
+            } else {
+                (last_owner.clone(), 0)
+            };
+            last_owner = owner.clone();
+
+            let mut ttl_text = None;
+            let mut typ = None;
+            for t in rest.by_ref() {
+                if is_class(&t.text) {
+                    continue;
+                }
+                if t.text.starts_with(|c: char| c.is_ascii_digit()) {
+                    ttl_text = Some(t.text);
+                    continue;
+                }
+                typ = Some(t.text.to_ascii_uppercase());
+                break;
+            }
+            let Some(typ) = typ else { continue };
+            out.push(Entry {
+                first_line,
+                last_line: i - 1,
+                explicit_owner,
+                owner_end,
+                origin: origin.clone(),
+                comment,
+                owner,
+                ttl: ttl_text.as_deref().and_then(|t| t.parse().ok()),
+                ttl_text,
+                typ,
+                rdata: rest.collect(),
+            });
+        }
+        out
+    }
+
+    /// Records `owner` (absolute) / `typ`.
+    pub fn find(&self, owner: &str, typ: &str) -> Vec<Entry> {
+        let owner = owner.to_ascii_lowercase();
+        self.entries()
+            .into_iter()
+            .filter(|e| e.owner == owner && e.typ.eq_ignore_ascii_case(typ))
+            .collect()
+    }
+
+    /// Make `value` the only `typ` record of `owner` (absolute); `ttl` `None`
+    /// leaves the TTL to `$TTL` (or keeps the one already written). Returns
+    /// whether anything changed.
+    pub fn set(&mut self, owner: &str, typ: &str, value: &str, ttl: Option<u32>) -> bool {
+        let found = self.find(owner, typ);
+        if let [only] = found.as_slice()
+            && only.value() == value
+            && (ttl.is_none() || only.ttl == ttl)
+        {
+            return false;
+        }
+
+        match found.split_first() {
+            Some((first, rest)) => {
+                let owner_field = self.lines[first.first_line][..first.owner_end].to_owned();
+                let mut line = record_line(
+                    &owner_field,
+                    ttl.map(|t| t.to_string())
+                        .or_else(|| first.ttl_text.clone()),
+                    typ,
+                    value,
+                );
+                if let Some(c) = &first.comment {
+                    line = format!("{line} {c}");
+                }
+                // later lines first, so earlier indices stay valid
+                for e in rest.iter().rev() {
+                    self.remove(e);
+                }
+                self.lines
+                    .splice(first.first_line..=first.last_line, [line]);
+            }
+            None => {
+                let all = self.entries();
+                let (at, origin) = match all.iter().rev().find(|e| e.owner == owner) {
+                    Some(e) => (e.last_line + 1, e.origin.clone()),
+                    None => {
+                        // before the trailing newline, if any
+                        let end = match self.lines.last() {
+                            Some(l) if l.is_empty() => self.lines.len() - 1,
+                            _ => self.lines.len(),
+                        };
+                        let origin = all
+                            .last()
+                            .map_or_else(|| self.origin.clone(), |e| e.origin.clone());
+                        (end, origin)
+                    }
+                };
+                let line = record_line(
+                    &relative(owner, &origin),
+                    ttl.map(|t| t.to_string()),
+                    typ,
+                    value,
+                );
+                self.lines.insert(at, line);
+            }
+        }
+        true
+    }
+
+    /// Drop every `typ` record of `owner` (absolute); returns how many.
+    pub fn delete(&mut self, owner: &str, typ: &str) -> usize {
+        let found = self.find(owner, typ);
+        for e in found.iter().rev() {
+            self.remove(e);
+        }
+        found.len()
+    }
+
+    /// Remove `e`, handing its owner on to a following record that
+    /// inherited it.
+    fn remove(&mut self, e: &Entry) {
+        if e.explicit_owner {
+            let next = self
+                .entries()
+                .into_iter()
+                .find(|n| n.first_line > e.last_line);
+            if let Some(n) = next
+                && !n.explicit_owner
+            {
+                let owner = self.lines[e.first_line][..e.owner_end].to_owned();
+                self.lines[n.first_line] = format!("{owner}{}", self.lines[n.first_line]);
+            }
+        }
+        self.lines.drain(e.first_line..=e.last_line);
+    }
+
+    /// Advance the SOA serial in place; returns the new serial.
+    pub fn bump_serial(&mut self, mode: SerialMode, today: NaiveDate) -> Option<u32> {
+        let soa = self.entries().into_iter().find(|e| e.typ == "SOA")?;
+        // MNAME RNAME SERIAL REFRESH RETRY EXPIRE MINIMUM
+        let tok = soa.rdata.get(2)?;
+        let new = next_serial(tok.text.parse().ok()?, mode, today);
+        self.lines[tok.line].replace_range(tok.start..tok.end, &new.to_string());
+        Some(new)
+    }
+
+    /// Absolute name of `e` relative to the zone apex (`@` for the apex).
+    pub fn name_of(&self, e: &Entry) -> String {
+        relative(&e.owner, &self.origin)
+    }
+}
+
+/// `name` relative to `origin` if below it, absolute otherwise.
+fn relative(name: &str, origin: &str) -> String {
+    if name == origin {
+        "@".into()
+    } else if let Some(n) = name.strip_suffix(&format!(".{origin}")) {
+        n.to_owned()
+    } else {
+        name.to_owned()
+    }
+}
+
+fn record_line(owner: &str, ttl: Option<String>, typ: &str, value: &str) -> String {
+    match ttl {
+        Some(t) => format!("{owner}\t{t}\tIN\t{typ}\t{value}"),
+        None => format!("{owner}\tIN\t{typ}\t{value}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ZONE: &str = "\
+$ORIGIN example.com.
+$TTL 3600
+@   IN SOA ns1.example.com. hostmaster.example.com. (
+        2024010101 ; serial
+        7200 3600 1209600 300 )
+    IN NS  ns1.example.com.
+www 300 IN A 192.0.2.1 ; web
+home    IN A 192.0.2.7
+        IN AAAA 2001:db8::7
+txt     IN TXT \"a;b\" \"c\"
+";
+
+    fn day() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()
+    }
+
+    #[test]
+    fn parses_records() {
+        let z = Zone::parse(ZONE, "example.com");
+        let e = z.entries();
+        let kinds: Vec<_> = e
+            .iter()
+            .map(|e| (e.owner.as_str(), e.typ.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("example.com.", "SOA"),
+                ("example.com.", "NS"),
+                ("www.example.com.", "A"),
+                ("home.example.com.", "A"),
+                ("home.example.com.", "AAAA"),
+                ("txt.example.com.", "TXT"),
+            ]
+        );
+        assert_eq!(e[2].ttl, Some(300));
+        assert_eq!(e[5].value(), "\"a;b\" \"c\"");
+        assert_eq!(z.name_of(&e[1]), "@");
+    }
+
+    #[test]
+    fn set_replaces_and_inserts() {
+        let mut z = Zone::parse(ZONE, "example.com");
+        assert!(!z.set("www.example.com.", "A", "192.0.2.1", Some(300)));
+        assert!(z.set("www.example.com.", "A", "192.0.2.9", None));
+        assert!(z.render().contains("www\t300\tIN\tA\t192.0.2.9 ; web\n"));
+
+        assert!(z.set("home.example.com.", "AAAA", "2001:db8::8", Some(60)));
+        assert!(
+            z.render()
+                .contains("home    IN A 192.0.2.7\n\t60\tIN\tAAAA\t2001:db8::8\n")
+        );
+
+        assert!(z.set("new.example.com.", "A", "192.0.2.3", None));
+        assert!(z.render().ends_with("new\tIN\tA\t192.0.2.3\n"));
+
+        // deleting an owner line hands the owner on to the inheriting record
+        assert_eq!(z.delete("home.example.com.", "A"), 1);
+        assert_eq!(
+            z.find("home.example.com.", "AAAA")[0].value(),
+            "2001:db8::8"
+        );
+    }
+
+    #[test]
+    fn serials() {
+        assert_eq!(next_serial(2024010101, SerialMode::Date, day()), 2026101800);
+        assert_eq!(next_serial(2026101800, SerialMode::Date, day()), 2026101801);
+        assert_eq!(next_serial(7, SerialMode::Increment, day()), 8);
+        assert_eq!(next_serial(u32::MAX, SerialMode::Increment, day()), 0);
+
+        let mut z = Zone::parse(ZONE, "example.com");
+        assert_eq!(z.bump_serial(SerialMode::Date, day()), Some(2026101800));
+        assert!(z.render().contains("        2026101800 ; serial\n"));
+    }
+}
@@ -0,0 +1,79 @@
+//! Helpers for providers that edit local files (zone files, hosts files)
+
+use crate::ProviderError;
+use std::{
+    fs, io,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use tracing::debug;
+
+/// Serializes read-modify-write cycles; several entries (A + AAAA, …) may
+/// share one file.
+pub static EDIT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Classify a failed file operation on `path`.
+pub fn io_error(path: &Path, e: io::Error) -> ProviderError {
+    let msg = format!("`{}`: {e}", path.display());
+    match e.kind() {
+        io::ErrorKind::NotFound => ProviderError::NotFound(msg),
+        io::ErrorKind::PermissionDenied => ProviderError::Auth(msg),
+        _ => ProviderError::Transient(msg),
+    }
+}
+
+/// Write `body` atomically: `<path>.tmp` with the old file's mode, then
+/// rename. With `in_place_fallback`, a refused rename (a bind-mounted
+/// `/etc/hosts` in a container) falls back to overwriting `path` directly.
+pub fn write_atomic(path: &Path, body: &str, in_place_fallback: bool) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let mut f = fs::File::create(&tmp)?;
+    f.write_all(body.as_bytes())?;
+    f.sync_all()?;
+    if let Ok(meta) = fs::metadata(path) {
+        fs::set_permissions(&tmp, meta.permissions())?;
+    }
+    match fs::rename(&tmp, path) {
+        Err(e) if in_place_fallback => {
+            debug!(
+                "rename to `{}` failed ({e}); writing in place",
+                path.display()
+            );
+            let _ = fs::remove_file(&tmp);
+            fs::write(path, body)
+        }
+        res => res,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn replaces_and_keeps_mode() {
+        let dir = env::temp_dir().join(format!("ddns-provider-file-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hosts");
+        fs::write(&path, "old").unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions();
+
+        write_atomic(&path, "new", false).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::metadata(&path).unwrap().permissions(), mode);
+        assert!(!dir.join("hosts.tmp").exists());
+
+        let missing = dir.join("gone").join("hosts");
+        let err = write_atomic(&missing, "x", true).unwrap_err();
+        assert!(matches!(
+            io_error(&missing, err),
+            ProviderError::NotFound(_)
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,18 +1,186 @@
+pub mod file;
+pub mod name;
+
 use async_trait::async_trait;
+use reqwest::{
+    StatusCode,
+    header::{HeaderMap, RETRY_AFTER},
+};
+use serde::de::DeserializeOwned;
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 use thiserror::Error;
 
-#[derive(Clone, Copy, Debug)]
+/// Provider-specific identifiers (zone id, record id, …) keyed by name.
+///
+/// Handed out by [`DnsProvider::cache`] and fed back through
+/// [`DnsProvider::restore_cache`] so lookups survive a restart.
+pub type ProviderCache = BTreeMap<String, String>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RecordType {
     A,
     AAAA,
+    TXT,
+    CNAME,
+    HTTPS,
+    SVCB,
+}
+
+impl RecordType {
+    pub const ALL: [RecordType; 6] = [
+        RecordType::A,
+        RecordType::AAAA,
+        RecordType::TXT,
+        RecordType::CNAME,
+        RecordType::HTTPS,
+        RecordType::SVCB,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecordType::A => "A",
+            RecordType::AAAA => "AAAA",
+            RecordType::TXT => "TXT",
+            RecordType::CNAME => "CNAME",
+            RecordType::HTTPS => "HTTPS",
+            RecordType::SVCB => "SVCB",
+        }
+    }
+
+    /// `A` / `AAAA` – the value is an IP address.
+    pub fn is_address(&self) -> bool {
+        matches!(self, RecordType::A | RecordType::AAAA)
+    }
+}
+
+impl std::fmt::Display for RecordType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for RecordType {
+    type Err = ProviderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ProviderError::InvalidInput(format!("unsupported record type `{s}`")))
+    }
+}
+
+/// A record as reported by [`DnsProvider::list_records`].
+#[derive(Clone, Debug)]
+pub struct DnsRecord {
+    /// provider-side identifier
+    pub id: String,
+    /// name relative to the zone, `@` for the apex
+    pub name: String,
+    pub typ: RecordType,
+    pub value: String,
+    pub ttl: u32,
 }
 
 #[derive(Error, Debug)]
 pub enum ProviderError {
     #[error("http error: {0}")]
     Http(#[from] reqwest::Error),
+    /// Unclassified API failure; retried.
     #[error("api error: {0}")]
     Api(String),
+    /// Bad or insufficient credentials; never retried.
+    #[error("auth error: {0}")]
+    Auth(String),
+    /// Zone or record does not exist; never retried.
+    #[error("not found: {0}")]
+    NotFound(String),
+    /// Throttled by the API; retried after `retry_after` when given.
+    #[error("rate limited{}", fmt_retry_after(.retry_after))]
+    RateLimited { retry_after: Option<Duration> },
+    /// Request rejected as malformed (bad value, TTL, …); never retried.
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    /// Temporary server-side failure; retried.
+    #[error("transient error: {0}")]
+    Transient(String),
+    #[error("operation not supported: {0}")]
+    Unsupported(&'static str),
+}
+
+fn fmt_retry_after(d: &Option<Duration>) -> String {
+    d.map(|d| format!(" (retry after {}s)", d.as_secs()))
+        .unwrap_or_default()
+}
+
+impl ProviderError {
+    /// Classify a failed HTTP exchange by status code when the API body
+    /// carries nothing more specific.
+    pub fn from_status(status: StatusCode, msg: String, retry_after: Option<Duration>) -> Self {
+        match status.as_u16() {
+            401 | 403 => ProviderError::Auth(msg),
+            404 => ProviderError::NotFound(msg),
+            429 => ProviderError::RateLimited { retry_after },
+            400 | 409 | 422 => ProviderError::InvalidInput(msg),
+            500..=599 => ProviderError::Transient(msg),
+            _ => ProviderError::Api(msg),
+        }
+    }
+
+    /// `false` for failures that will not go away by trying again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ProviderError::Http(e) => e
+                .status()
+                .is_none_or(|s| s.is_server_error() || s == StatusCode::TOO_MANY_REQUESTS),
+            ProviderError::Api(_)
+            | ProviderError::RateLimited { .. }
+            | ProviderError::Transient(_) => true,
+            ProviderError::Auth(_)
+            | ProviderError::NotFound(_)
+            | ProviderError::InvalidInput(_)
+            | ProviderError::Unsupported(_) => false,
+        }
+    }
+
+    /// Server-requested delay before the next attempt.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ProviderError::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+/// Parse a `Retry-After: <seconds>` header.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// `value` as a quoted TXT character-string (`"` and `\` escaped); values
+/// that are already quoted are passed through.
+pub fn quote_txt(value: &str) -> String {
+    if value.starts_with('"') {
+        return value.to_owned();
+    }
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Record value in master-file form, as most APIs store it: quoted TXT,
+/// absolute CNAME target; anything else unchanged.
+pub fn record_value(typ: RecordType, value: &str) -> String {
+    match typ {
+        RecordType::TXT => quote_txt(value),
+        RecordType::CNAME if !value.ends_with('.') => format!("{value}."),
+        _ => value.to_owned(),
+    }
 }
 
 #[async_trait]
@@ -22,12 +190,121 @@ pub trait DnsProvider: Send + Sync {
     fn record(&self) -> &str;
     fn record_type(&self) -> RecordType;
 
+    /// Whether `zone` / `name` / `typ` address the configured record, the
+    /// one whose identifiers are cached.
+    fn is_configured(&self, zone: &str, name: &str, typ: RecordType) -> bool {
+        zone == self.zone() && name == self.record() && typ == self.record_type()
+    }
+
+    /// Snapshot of cached identifiers; empty by default.
+    fn cache(&self) -> ProviderCache {
+        ProviderCache::new()
+    }
+
+    /// Seed cached identifiers before the first call; ignored by default.
+    fn restore_cache(&self, _cache: &ProviderCache) {}
+
+    /// Current value of the `name` / `typ` record in `zone`, `None` when it
+    /// does not exist.
+    ///
+    /// Providers that cannot read records keep the default, which returns
+    /// [`ProviderError::Unsupported`].
+    async fn get_record(
+        &self,
+        _zone: &str,
+        _name: &str,
+        _typ: RecordType,
+    ) -> Result<Option<String>, ProviderError> {
+        Err(ProviderError::Unsupported("get_record"))
+    }
+
+    /// All records in `zone` whose type is a [`RecordType`]; others are skipped.
+    async fn list_records(&self, _zone: &str) -> Result<Vec<DnsRecord>, ProviderError> {
+        Err(ProviderError::Unsupported("list_records"))
+    }
+
+    /// Remove every record matching `name` and `typ` in `zone`.
+    async fn delete_record(
+        &self,
+        _zone: &str,
+        _name: &str,
+        _typ: RecordType,
+    ) -> Result<(), ProviderError> {
+        Err(ProviderError::Unsupported("delete_record"))
+    }
+
     async fn upsert_record(
         &self,
         zone: &str,
         name: &str,
         typ: RecordType,
-        ip: &str,
+        value: &str,
         ttl: u32,
     ) -> Result<(), ProviderError>;
 }
+
+/*──────── factories ────────*/
+
+/// Provider-specific keys of a `[[provider]]` entry (everything besides the
+/// common fields in [`ProviderSpec`]).
+pub type ProviderTable = serde_json::Map<String, serde_json::Value>;
+
+/// Settings shared by every `[[provider]]` entry.
+#[derive(Clone, Copy, Debug)]
+pub struct ProviderSpec<'a> {
+    pub zone: &'a str,
+    pub record: &'a str,
+    pub record_type: &'a str,
+    /// `0` leaves the TTL to the provider's default
+    pub ttl: u32,
+}
+
+/// Constructor for one provider `kind`, registered with the core's
+/// provider registry.
+pub trait ProviderFactory: Send + Sync {
+    /// Value of `kind` in the config, matched case-insensitively.
+    fn kind(&self) -> &'static str;
+
+    fn build(
+        &self,
+        spec: &ProviderSpec<'_>,
+        table: &ProviderTable,
+    ) -> anyhow::Result<Arc<dyn DnsProvider>>;
+}
+
+/// Deserialize the provider-specific table into a typed config struct.
+pub fn parse_table<T: DeserializeOwned>(
+    kind: &str,
+    table: &ProviderTable,
+) -> Result<T, ProviderError> {
+    serde_json::from_value(serde_json::Value::Object(table.clone()))
+        .map_err(|e| ProviderError::InvalidInput(format!("{kind}: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_values() {
+        assert_eq!(quote_txt("v=spf1 -all"), r#""v=spf1 -all""#);
+        assert_eq!(quote_txt(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(quote_txt(r"ends\"), r#""ends\\""#);
+        assert_eq!(quote_txt(r#""already" "split""#), r#""already" "split""#);
+
+        assert_eq!(record_value(RecordType::TXT, "a b"), r#""a b""#);
+        assert_eq!(
+            record_value(RecordType::CNAME, "t.example.net"),
+            "t.example.net."
+        );
+        assert_eq!(
+            record_value(RecordType::CNAME, "t.example.net."),
+            "t.example.net."
+        );
+        assert_eq!(record_value(RecordType::A, "192.0.2.1"), "192.0.2.1");
+        assert_eq!(
+            record_value(RecordType::HTTPS, "1 . alpn=h2"),
+            "1 . alpn=h2"
+        );
+    }
+}
@@ -0,0 +1,65 @@
+//! Record names: relative (`home`, `@` for the apex) ↔ fully qualified
+//!
+//! Names handed to a provider are relative to the zone; a trailing dot on
+//! them or on the zone is ignored. DNS names compare case-insensitively.
+
+/// `home` in `example.com` → `home.example.com` (`@` → the zone itself).
+pub fn fqdn(name: &str, zone: &str) -> String {
+    let zone = zone.trim_end_matches('.');
+    match name.trim_end_matches('.') {
+        "@" | "" => zone.to_owned(),
+        name => format!("{name}.{zone}"),
+    }
+}
+
+/// [`fqdn`] with the trailing dot of an absolute name (`home.example.com.`).
+pub fn absolute(name: &str, zone: &str) -> String {
+    format!("{}.", fqdn(name, zone))
+}
+
+/// `home.example.com.` in `example.com` → `home`, the apex → `@`; names
+/// outside `zone` are returned without their trailing dot.
+pub fn relative(name: &str, zone: &str) -> String {
+    let name = name.trim_end_matches('.');
+    let zone = zone.trim_end_matches('.');
+    if name.eq_ignore_ascii_case(zone) {
+        return "@".into();
+    }
+    name.len()
+        .checked_sub(zone.len())
+        .and_then(|i| name.get(..i).zip(name.get(i..)))
+        .filter(|(_, tail)| tail.eq_ignore_ascii_case(zone))
+        .and_then(|(head, _)| head.strip_suffix('.'))
+        .unwrap_or(name)
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qualified() {
+        assert_eq!(fqdn("@", "example.com"), "example.com");
+        assert_eq!(fqdn("", "example.com."), "example.com");
+        assert_eq!(fqdn("home", "example.com."), "home.example.com");
+        assert_eq!(fqdn("home.", "example.com"), "home.example.com");
+        assert_eq!(absolute("@", "example.com."), "example.com.");
+        assert_eq!(absolute("a.b", "example.com"), "a.b.example.com.");
+    }
+
+    #[test]
+    fn relative_names() {
+        assert_eq!(relative("example.com.", "example.com"), "@");
+        assert_eq!(relative("Example.COM", "example.com."), "@");
+        assert_eq!(relative("home.example.com.", "example.com."), "home");
+        assert_eq!(relative("Home.Example.com.", "example.COM"), "Home");
+        assert_eq!(relative("a.b.example.com", "example.com"), "a.b");
+        assert_eq!(
+            relative("home.example.org.", "example.com"),
+            "home.example.org"
+        );
+        assert_eq!(relative("notexample.com.", "example.com"), "notexample.com");
+        assert_eq!(relative("home", "example.com"), "home");
+    }
+}
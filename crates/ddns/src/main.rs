@@ -1,11 +1,14 @@
 //! Command-line entry point for **ddns**
 //!
 //! * Parses a single `--config` option (or `DDNS_CONFIG` env var)  
+//! * `ddns records list|delete` runs one-shot record maintenance  
 //! * Sets up tracing with a compact formatter  
 //! * Boots the core logic defined in `ddns_core`
 
+mod records;
+
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use ddns_core::{bootstrap, load_config};
 use tracing_subscriber::{filter::EnvFilter, fmt, prelude::*};
 
@@ -16,6 +19,19 @@ struct Cli {
     /// Path to the config file (optional; environment variables are used if absent)
     #[arg(short, long, env = "DDNS_CONFIG", default_value = "ddns.toml")]
     config: String,
+
+    /// Without a subcommand the daemon is started
+    #[command(subcommand)]
+    cmd: Option<Cmd>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Cmd {
+    /// Inspect or remove records on the configured providers
+    Records {
+        #[command(subcommand)]
+        action: records::RecordsCmd,
+    },
 }
 
 #[tokio::main]
@@ -30,5 +46,8 @@ async fn main() -> Result<()> {
         .init();
 
     let cfg = load_config(&cli.config)?;
-    bootstrap(cfg).await
+    match cli.cmd {
+        Some(Cmd::Records { action }) => records::run(cfg, action).await,
+        None => bootstrap(cfg).await,
+    }
 }
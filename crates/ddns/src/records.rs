@@ -0,0 +1,141 @@
+//! `ddns records …` – one-shot record maintenance on configured providers
+
+use anyhow::{Result, bail};
+use clap::Subcommand;
+use ddns_core::{
+    ProviderRegistry,
+    cfg::{AppConfig, ProviderCfg},
+    display_key,
+};
+use ddns_provider::{ProviderError, RecordType, name::fqdn};
+
+#[derive(Subcommand, Debug)]
+pub enum RecordsCmd {
+    /// List the records in each provider's zone (all supported types)
+    List {
+        /// Only this provider (alias or key as listed, or kind)
+        #[arg(short, long)]
+        provider: Option<String>,
+    },
+    /// Delete a record from one provider
+    Delete {
+        /// Provider alias or key as listed, or kind when unambiguous
+        #[arg(short, long)]
+        provider: String,
+        /// Record name relative to the zone; defaults to the configured record
+        #[arg(short, long)]
+        record: Option<String>,
+        /// Record type; defaults to the configured `record_type`
+        #[arg(short = 't', long = "type")]
+        record_type: Option<String>,
+        /// Zone; defaults to the configured zone
+        #[arg(short, long)]
+        zone: Option<String>,
+    },
+}
+
+pub async fn run(cfg: AppConfig, cmd: RecordsCmd) -> Result<()> {
+    let registry = ProviderRegistry::new();
+    match cmd {
+        RecordsCmd::List { provider } => {
+            let mut found = false;
+            let mut failed = 0;
+            // entries sharing kind, zone and credentials (A + AAAA) list once
+            let mut seen: Vec<&ProviderCfg> = Vec::new();
+            for p in &cfg.provider {
+                let key = display_key(p);
+                if provider.as_deref().is_some_and(|want| !selects(p, want)) {
+                    continue;
+                }
+                found = true;
+                if seen.iter().any(|q| same_zone(p, q)) {
+                    continue;
+                }
+                seen.push(p);
+                let records = match registry.build(p) {
+                    Ok(prov) => prov.list_records(&p.zone).await.map_err(Into::into),
+                    Err(e) => Err(e),
+                };
+                match records {
+                    Ok(records) => {
+                        for r in records {
+                            println!(
+                                "{key}\t{}\t{}\t{}\t{}",
+                                fqdn(&r.name, &p.zone),
+                                r.typ,
+                                r.ttl,
+                                r.value
+                            );
+                        }
+                    }
+                    Err(e)
+                        if matches!(
+                            e.downcast_ref::<ProviderError>(),
+                            Some(ProviderError::Unsupported(_))
+                        ) =>
+                    {
+                        println!("{key}\tlisting not supported by `{}`", p.kind);
+                    }
+                    Err(e) => {
+                        eprintln!("{key}\terror: {e:#}");
+                        failed += 1;
+                    }
+                }
+            }
+            if !found {
+                bail!("no provider matches `{}`", provider.unwrap_or_default());
+            }
+            if failed > 0 {
+                bail!("{failed} provider(s) could not be listed");
+            }
+        }
+        RecordsCmd::Delete {
+            provider,
+            record,
+            record_type,
+            zone,
+        } => {
+            let p = match cfg
+                .provider
+                .iter()
+                .filter(|p| selects(p, &provider))
+                .collect::<Vec<_>>()
+                .as_slice()
+            {
+                [p] => *p,
+                [] => bail!("no provider matches `{provider}`"),
+                many => bail!(
+                    "`{provider}` matches several providers: {}",
+                    many.iter()
+                        .map(|p| display_key(p))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            };
+            let prov = registry.build(p)?;
+            let zone = zone.unwrap_or_else(|| p.zone.clone());
+            let name = record.unwrap_or_else(|| p.record.clone());
+            let typ = match record_type {
+                Some(t) => t.parse::<RecordType>()?,
+                None => prov.record_type(),
+            };
+            prov.delete_record(&zone, &name, typ).await?;
+            println!("{provider}\tdeleted {typ} {}", fqdn(&name, &zone));
+        }
+    }
+    Ok(())
+}
+
+/// `want` names `p` by its key, or by kind when `p` has no alias.
+fn selects(p: &ProviderCfg, want: &str) -> bool {
+    display_key(p) == want || (p.alias.is_none() && p.kind.eq_ignore_ascii_case(want))
+}
+
+/// Same driver, zone and driver settings: listing both would repeat the zone.
+fn same_zone(a: &ProviderCfg, b: &ProviderCfg) -> bool {
+    a.kind.eq_ignore_ascii_case(&b.kind)
+        && a.zone
+            .trim_end_matches('.')
+            .eq_ignore_ascii_case(b.zone.trim_end_matches('.'))
+        && a.params == b.params
+}